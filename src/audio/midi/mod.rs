@@ -2,9 +2,9 @@ mod message;
 
 pub use message::Message;
 
-use crate::{config::MidiSlider, Result};
+use crate::{config::MidiSlider, util::log, Result};
 use anyhow::anyhow;
-use midir::{MidiInput, MidiInputPort};
+use midir::{MidiInput, MidiInputPort, MidiOutput};
 use num_traits::Num;
 use std::sync::mpsc::Sender;
 use std::sync::mpsc::{self, Receiver};
@@ -24,6 +24,50 @@ pub fn listen_for_input(port_name: &str) -> Result<Receiver<Message>> {
     Ok(receiver)
 }
 
+/// Connects to the provided output port. Returns a sender, and any messages sent on it are written
+/// to the port from a separate thread, so that the audio thread never waits on the MIDI driver.
+pub fn connect_output(port_name: &str) -> Result<Sender<MidiMessage<'static>>> {
+    let midi_output = midi_output()?;
+    let port = midi_output
+        .ports()
+        .into_iter()
+        .find(|port| midi_output.port_name(port) == Ok(port_name.into()))
+        .ok_or_else(|| {
+            anyhow!(
+                "Could not find a MIDI output port with name '{}'",
+                port_name
+            )
+        })?;
+    let mut connection = midi_output
+        .connect(&port, "midir-write-output")
+        .map_err(|error| {
+            anyhow!(
+                "Could not connect to MIDI output '{}': {}",
+                port_name,
+                error
+            )
+        })?;
+
+    println!("MIDI output: {}", port_name);
+
+    let (sender, receiver) = mpsc::channel::<MidiMessage<'static>>();
+
+    thread::spawn(move || {
+        let mut bytes = [0; 3];
+
+        for message in receiver {
+            // system exclusive messages don't fit, but nothing sends them
+            if let Ok(length) = message.copy_to_slice(&mut bytes) {
+                if let Err(error) = connection.send(&bytes[..length]) {
+                    log::error(format!("Could not send MIDI message: {}", error));
+                }
+            }
+        }
+    });
+
+    Ok(sender)
+}
+
 pub fn port_names() -> Result<Vec<String>> {
     let midi_input = midi_input()?;
     midi_input
@@ -49,6 +93,29 @@ pub fn latest_control_value(slider: MidiSlider, messages: &[Message]) -> Option<
         })
 }
 
+/// Get the last value for a `MidiSlider` from a list of messages, mapped into a range.
+pub fn latest_slider_value<T: Num + From<u8> + Copy>(
+    slider: MidiSlider,
+    min: T,
+    max: T,
+    messages: &[Message],
+) -> Option<T> {
+    let control_value = latest_control_value(slider, messages)?;
+    Some(interpolate_control_value(min, max, control_value))
+}
+
+/// Get the last value for a `MidiSlider` from a list of messages, mapped exponentially into a
+/// range. Useful for frequencies, where a linear mapping would crowd the low end.
+pub fn latest_slider_value_exponential(
+    slider: MidiSlider,
+    min: f32,
+    max: f32,
+    messages: &[Message],
+) -> Option<f32> {
+    let position = latest_slider_value(slider, 0.0, 1.0, messages)?;
+    Some(min * (max / min).powf(position))
+}
+
 /// Maps a control value (0-127) into a range.
 /// eg. `control_value_in_range(50, 100, 64) == 75`
 pub fn interpolate_control_value<T: Num + From<u8> + Copy>(
@@ -67,6 +134,10 @@ fn midi_input() -> Result<MidiInput> {
     Ok(MidiInput::new("Input")?)
 }
 
+fn midi_output() -> Result<MidiOutput> {
+    Ok(MidiOutput::new("Output")?)
+}
+
 fn port(name: &str) -> Result<MidiInputPort> {
     let names = port_names()?;
     let midi_input = midi_input()?;
@@ -1,13 +1,19 @@
 pub mod midi;
 
-use crate::{config, effect, ring_buffer, Result};
+use crate::{config, effect, ring_buffer, util::log, Result};
 use anyhow::anyhow;
 use cpal::{
     traits::{DeviceTrait, HostTrait, StreamTrait},
-    Device, Host, StreamConfig,
+    Device, Host, Stream, StreamConfig,
 };
 use ringbuf::RingBuffer;
-use std::time::Duration;
+use std::{sync::mpsc::Receiver, time::Duration};
+
+/// The running input and output streams. Audio stops when they are dropped.
+pub struct Streams {
+    _input: Stream,
+    _output: Stream,
+}
 
 pub fn run(
     latency_ms: u32,
@@ -15,8 +21,38 @@ pub fn run(
     output_device: &Device,
     config: &StreamConfig,
     midi_config: &config::Midi,
-    mut effect: effect::Boxed,
+    effect: effect::Boxed,
 ) -> Result<()> {
+    let midi_messages = match &midi_config.port {
+        Some(port_name) => Some(midi::listen_for_input(port_name)?),
+        None => None,
+    };
+
+    let streams = start(
+        latency_ms,
+        input_device,
+        output_device,
+        config,
+        midi_messages,
+        effect,
+    )?;
+
+    std::thread::sleep(Duration::from_secs(u64::MAX));
+
+    drop(streams);
+
+    Ok(())
+}
+
+/// Starts processing audio in the background, with MIDI messages from the provided receiver.
+pub fn start(
+    latency_ms: u32,
+    input_device: &Device,
+    output_device: &Device,
+    config: &StreamConfig,
+    midi_messages: Option<Receiver<midi::Message>>,
+    mut effect: effect::Boxed,
+) -> Result<Streams> {
     let latency_num_frames = (latency_ms as f32 / 1_000.0) * config.sample_rate.0 as f32;
     let latency_num_samples = latency_num_frames as usize * config.channels as usize;
 
@@ -24,14 +60,9 @@ pub fn run(
     let (mut producer, mut consumer) = ring.split();
     ring_buffer::write_empty_samples(&mut producer, latency_num_samples)?;
 
-    let midi_messages = match &midi_config.port {
-        Some(port_name) => Some(midi::listen_for_input(port_name)?),
-        None => None,
-    };
-
     let input_data_fn = move |data: &[f32], _: &cpal::InputCallbackInfo| {
         if let Err(e) = ring_buffer::write_samples(&mut producer, data) {
-            eprintln!("input: {:?}", e);
+            log::error(format!("input: {:?}", e));
         }
     };
 
@@ -43,7 +74,7 @@ pub fn run(
         if let Err(e) = ring_buffer::read_samples(&mut consumer, output.len())
             .and_then(|frame| effect.process(&midi_messages, &frame, output))
         {
-            eprintln!("output: {:?}", e);
+            log::error(format!("output: {:?}", e));
         };
     };
 
@@ -62,12 +93,10 @@ pub fn run(
     input_stream.play()?;
     output_stream.play()?;
 
-    std::thread::sleep(Duration::from_secs(u64::MAX));
-
-    drop(input_stream);
-    drop(output_stream);
-
-    Ok(())
+    Ok(Streams {
+        _input: input_stream,
+        _output: output_stream,
+    })
 }
 
 pub fn devices(config: &config::Audio) -> Result<(Device, Device)> {
@@ -105,7 +134,7 @@ pub fn device(host: &Host, name: &str) -> Result<Device> {
         .collect::<Result<Vec<String>>>()?;
 
     host.devices()?
-        .find(|device| device.name().map_or(false, |n| n == name))
+        .find(|device| device.name().is_ok_and(|n| n == name))
         .ok_or_else(|| {
             anyhow!(
                 "Could not find an audio device with name '{}'. Available devices are:\n{}",
@@ -120,5 +149,5 @@ pub fn config(device: &Device) -> Result<StreamConfig> {
 }
 
 fn handle_error(error: cpal::StreamError) {
-    eprintln!("an error occurred on stream: {}", error);
+    log::error(format!("an error occurred on stream: {}", error));
 }
@@ -0,0 +1,283 @@
+use crate::{audio_unit::AudioUnit, Result};
+use cpal::StreamConfig;
+use serde::Deserialize;
+use std::{
+    f32::consts::PI,
+    sync::mpsc::{self, Receiver, Sender},
+};
+use Message::*;
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub enum Kind {
+    LowPass,
+    HighPass,
+    BandPass,
+    Notch,
+    AllPass,
+    LowShelf,
+    HighShelf,
+    Peak,
+}
+
+#[derive(Debug)]
+pub enum Message {
+    SetFrequency(f32),
+    SetGain(f32),
+    SetQ(f32),
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Parameters {
+    pub kind: Kind,
+    pub frequency: f32,
+    pub q: f32,
+    pub gain_db: f32,
+}
+
+impl Parameters {
+    pub const BUTTERWORTH_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+    pub fn new(kind: Kind, frequency: f32, q: f32, gain_db: f32) -> Self {
+        Self {
+            kind,
+            frequency,
+            q,
+            gain_db,
+        }
+    }
+}
+
+/// Normalized coefficients, calculated with the formulas from Robert Bristow-Johnson's
+/// "Audio EQ Cookbook".
+#[derive(Copy, Clone, Debug)]
+pub struct Coefficients {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Coefficients {
+    pub fn new(parameters: &Parameters, sample_rate: f32) -> Self {
+        let frequency = parameters.frequency.clamp(1.0, sample_rate * 0.49);
+        let q = parameters.q.max(0.01);
+
+        let w0 = 2.0 * PI * frequency / sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q);
+        let a = 10.0_f32.powf(parameters.gain_db / 40.0);
+
+        let (b0, b1, b2, a0, a1, a2) = match parameters.kind {
+            Kind::LowPass => (
+                (1.0 - cos) / 2.0,
+                1.0 - cos,
+                (1.0 - cos) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos,
+                1.0 - alpha,
+            ),
+            Kind::HighPass => (
+                (1.0 + cos) / 2.0,
+                -(1.0 + cos),
+                (1.0 + cos) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos,
+                1.0 - alpha,
+            ),
+            Kind::BandPass => (alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            Kind::Notch => (1.0, -2.0 * cos, 1.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            Kind::AllPass => (
+                1.0 - alpha,
+                -2.0 * cos,
+                1.0 + alpha,
+                1.0 + alpha,
+                -2.0 * cos,
+                1.0 - alpha,
+            ),
+            Kind::LowShelf => {
+                let sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+                (
+                    a * ((a + 1.0) - (a - 1.0) * cos + sqrt_a_alpha),
+                    2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                    a * ((a + 1.0) - (a - 1.0) * cos - sqrt_a_alpha),
+                    (a + 1.0) + (a - 1.0) * cos + sqrt_a_alpha,
+                    -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                    (a + 1.0) + (a - 1.0) * cos - sqrt_a_alpha,
+                )
+            }
+            Kind::HighShelf => {
+                let sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+                (
+                    a * ((a + 1.0) + (a - 1.0) * cos + sqrt_a_alpha),
+                    -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                    a * ((a + 1.0) + (a - 1.0) * cos - sqrt_a_alpha),
+                    (a + 1.0) - (a - 1.0) * cos + sqrt_a_alpha,
+                    2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                    (a + 1.0) - (a - 1.0) * cos - sqrt_a_alpha,
+                )
+            }
+            Kind::Peak => (
+                1.0 + alpha * a,
+                -2.0 * cos,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos,
+                1.0 - alpha / a,
+            ),
+        };
+
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+
+    /// The magnitude of the filter's response at a frequency.
+    pub fn gain_at(&self, frequency: f32, sample_rate: f32) -> f32 {
+        let w = 2.0 * PI * frequency / sample_rate;
+        let (sin1, cos1) = w.sin_cos();
+        let (sin2, cos2) = (2.0 * w).sin_cos();
+
+        let numerator_re = self.b0 + self.b1 * cos1 + self.b2 * cos2;
+        let numerator_im = -(self.b1 * sin1 + self.b2 * sin2);
+        let denominator_re = 1.0 + self.a1 * cos1 + self.a2 * cos2;
+        let denominator_im = -(self.a1 * sin1 + self.a2 * sin2);
+
+        (numerator_re.hypot(numerator_im)) / (denominator_re.hypot(denominator_im))
+    }
+}
+
+/// The delayed samples for a single channel, in transposed direct form II.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct State {
+    z1: f32,
+    z2: f32,
+}
+
+impl State {
+    pub fn process(&mut self, coefficients: &Coefficients, sample: f32) -> f32 {
+        let output = coefficients.b0 * sample + self.z1;
+        self.z1 = coefficients.b1 * sample - coefficients.a1 * output + self.z2;
+        self.z2 = coefficients.b2 * sample - coefficients.a2 * output;
+        output
+    }
+}
+
+pub struct Biquad {
+    parameters: Parameters,
+    sample_rate: f32,
+    coefficients: Coefficients,
+    states: Vec<State>,
+    messages: Receiver<Message>,
+}
+
+impl Biquad {
+    pub fn new(stream_config: &StreamConfig, parameters: Parameters) -> (Self, Sender<Message>) {
+        let (sender, receiver) = mpsc::channel();
+        let sample_rate = stream_config.sample_rate.0 as f32;
+
+        (
+            Self {
+                parameters,
+                sample_rate,
+                coefficients: Coefficients::new(&parameters, sample_rate),
+                states: vec![State::default(); stream_config.channels as usize],
+                messages: receiver,
+            },
+            sender,
+        )
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        if messages.is_empty() {
+            return;
+        }
+
+        for message in messages {
+            match message {
+                SetFrequency(frequency) => self.parameters.frequency = frequency,
+                SetGain(gain_db) => self.parameters.gain_db = gain_db,
+                SetQ(q) => self.parameters.q = q,
+            }
+        }
+
+        self.coefficients = Coefficients::new(&self.parameters, self.sample_rate);
+    }
+}
+
+impl AudioUnit for Biquad {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_messages();
+
+        let channels = self.states.len();
+
+        for (i, sample) in input.iter().enumerate() {
+            output[i] = self.states[i % channels].process(&self.coefficients, *sample);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    fn gain_db(kind: Kind, gain_db: f32, at: f32) -> f32 {
+        let parameters = Parameters::new(kind, 1_000.0, Parameters::BUTTERWORTH_Q, gain_db);
+        let coefficients = Coefficients::new(&parameters, SAMPLE_RATE);
+        20.0 * coefficients.gain_at(at, SAMPLE_RATE).log10()
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 0.1,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_low_pass() {
+        assert_close(gain_db(Kind::LowPass, 0.0, 10.0), 0.0);
+        assert_close(gain_db(Kind::LowPass, 0.0, 1_000.0), -3.0);
+        assert!(gain_db(Kind::LowPass, 0.0, 10_000.0) < -30.0);
+    }
+
+    #[test]
+    fn test_high_pass() {
+        assert!(gain_db(Kind::HighPass, 0.0, 100.0) < -30.0);
+        assert_close(gain_db(Kind::HighPass, 0.0, 1_000.0), -3.0);
+        assert_close(gain_db(Kind::HighPass, 0.0, 20_000.0), 0.0);
+    }
+
+    #[test]
+    fn test_peak() {
+        assert_close(gain_db(Kind::Peak, 6.0, 1_000.0), 6.0);
+        assert_close(gain_db(Kind::Peak, -6.0, 1_000.0), -6.0);
+        assert_close(gain_db(Kind::Peak, 6.0, 20.0), 0.0);
+    }
+
+    #[test]
+    fn test_all_pass() {
+        for frequency in [20.0, 1_000.0, 20_000.0] {
+            assert_close(gain_db(Kind::AllPass, 0.0, frequency), 0.0);
+        }
+    }
+
+    #[test]
+    fn test_shelves() {
+        assert_close(gain_db(Kind::LowShelf, 6.0, 20.0), 6.0);
+        assert_close(gain_db(Kind::LowShelf, 6.0, 20_000.0), 0.0);
+        assert_close(gain_db(Kind::HighShelf, 6.0, 20.0), 0.0);
+        assert_close(gain_db(Kind::HighShelf, 6.0, 20_000.0), 6.0);
+    }
+}
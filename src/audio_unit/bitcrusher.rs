@@ -0,0 +1,84 @@
+use crate::{audio_unit::AudioUnit, util::random::Random, Result};
+use std::sync::mpsc::{self, Receiver, Sender};
+use Message::*;
+
+#[derive(Debug)]
+pub enum Message {
+    SetBits(f32),
+}
+
+/// Reduces the bit depth of the signal by rounding each sample to the nearest of a small number of
+/// levels. Fractional bit depths are allowed, so that sweeping the depth is smooth.
+pub struct Bitcrusher {
+    bits: f32,
+    dither: bool,
+    random: Random,
+    messages: Receiver<Message>,
+}
+
+impl Bitcrusher {
+    pub fn new(bits: f32, dither: bool) -> (Self, Sender<Message>) {
+        let (sender, receiver) = mpsc::channel();
+
+        (
+            Self {
+                bits,
+                dither,
+                random: Random::from_time(),
+                messages: receiver,
+            },
+            sender,
+        )
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        for message in messages {
+            match message {
+                SetBits(bits) => self.bits = bits,
+            }
+        }
+    }
+
+    /// Triangular noise spanning two quantization steps, which decorrelates the rounding error
+    /// from the signal.
+    fn next_dither(&mut self) -> f32 {
+        if self.dither {
+            self.random.next_f32() - self.random.next_f32()
+        } else {
+            0.0
+        }
+    }
+}
+
+impl AudioUnit for Bitcrusher {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_messages();
+
+        // one bit is the sign, the rest count the levels either side of zero
+        let levels = 2.0_f32.powf(self.bits - 1.0);
+
+        for (i, sample) in input.iter().enumerate() {
+            let dither = self.next_dither();
+            output[i] = ((sample * levels + dither).round() / levels).clamp(-1.0, 1.0);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rounds_to_levels() {
+        let (mut bitcrusher, _) = Bitcrusher::new(3.0, false);
+        let input = [0.0, 0.1, 0.2, -0.3, 0.9];
+        let mut output = [0.0; 5];
+
+        bitcrusher.process(&input, &mut output).unwrap();
+
+        assert_eq!(output, [0.0, 0.0, 0.25, -0.25, 1.0]);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{
+    audio_unit::{AudioUnit, EnvelopeFollower},
+    util, Result,
+};
+use cpal::StreamConfig;
+use std::sync::mpsc::{self, Receiver, Sender};
+use Message::*;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Parameters {
+    pub threshold_db: f32,
+    pub ratio: f32,
+    pub knee_db: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+    pub makeup_db: f32,
+}
+
+impl Parameters {
+    /// The change in level, in dB, applied to a signal at `level_db`. Inside the knee, the ratio
+    /// is eased in quadratically.
+    pub fn gain_reduction_db(&self, level_db: f32) -> f32 {
+        let overshoot = level_db - self.threshold_db;
+        let slope = 1.0 / self.ratio - 1.0;
+
+        if 2.0 * overshoot <= -self.knee_db {
+            0.0
+        } else if 2.0 * overshoot.abs() <= self.knee_db {
+            slope * (overshoot + self.knee_db / 2.0).powi(2) / (2.0 * self.knee_db)
+        } else {
+            slope * overshoot
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Message {
+    SetThreshold(f32),
+    SetRatio(f32),
+    SetMakeup(f32),
+}
+
+pub struct Compressor {
+    parameters: Parameters,
+    channels: usize,
+    follower: EnvelopeFollower,
+    messages: Receiver<Message>,
+}
+
+impl Compressor {
+    pub fn new(stream_config: &StreamConfig, parameters: Parameters) -> (Self, Sender<Message>) {
+        let (sender, receiver) = mpsc::channel();
+        let sample_rate = stream_config.sample_rate.0 as f32;
+
+        (
+            Self {
+                parameters,
+                channels: stream_config.channels as usize,
+                follower: EnvelopeFollower::new(
+                    sample_rate,
+                    parameters.attack_ms,
+                    parameters.release_ms,
+                ),
+                messages: receiver,
+            },
+            sender,
+        )
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        for message in messages {
+            match message {
+                SetThreshold(threshold_db) => self.parameters.threshold_db = threshold_db,
+                SetRatio(ratio) => self.parameters.ratio = ratio,
+                SetMakeup(makeup_db) => self.parameters.makeup_db = makeup_db,
+            }
+        }
+    }
+
+    /// Compresses `input` based on the level of `sidechain`, which must be the same length.
+    pub fn process_with_sidechain(
+        &mut self,
+        sidechain: &[f32],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        self.process_messages();
+
+        for ((input, sidechain), output) in input
+            .chunks(self.channels)
+            .zip(sidechain.chunks(self.channels))
+            .zip(output.chunks_mut(self.channels))
+        {
+            let level_db = util::gain_to_db(self.follower.process_frame(sidechain));
+            let gain = util::db_to_gain(
+                self.parameters.gain_reduction_db(level_db) + self.parameters.makeup_db,
+            );
+
+            for (i, sample) in input.iter().enumerate() {
+                output[i] = sample * gain;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl AudioUnit for Compressor {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_with_sidechain(input, input, output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameters(knee_db: f32) -> Parameters {
+        Parameters {
+            threshold_db: -20.0,
+            ratio: 4.0,
+            knee_db,
+            attack_ms: 0.0,
+            release_ms: 0.0,
+            makeup_db: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_gain_reduction_hard_knee() {
+        let parameters = parameters(0.0);
+
+        assert_eq!(parameters.gain_reduction_db(-30.0), 0.0);
+        assert_eq!(parameters.gain_reduction_db(-20.0), 0.0);
+        assert_eq!(parameters.gain_reduction_db(-12.0), -6.0);
+        assert_eq!(parameters.gain_reduction_db(0.0), -15.0);
+    }
+
+    #[test]
+    fn test_gain_reduction_soft_knee() {
+        let parameters = parameters(10.0);
+
+        assert_eq!(parameters.gain_reduction_db(-26.0), 0.0);
+        assert!(parameters.gain_reduction_db(-20.0) < 0.0);
+        assert!(parameters.gain_reduction_db(-20.0) > -3.0);
+        assert_eq!(parameters.gain_reduction_db(0.0), -15.0);
+    }
+}
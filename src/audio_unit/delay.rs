@@ -1,7 +1,13 @@
-use crate::{audio_unit::AudioUnit, ring_buffer, util, Result};
+use crate::{
+    audio_unit::{AudioUnit, DelayLine},
+    ring_buffer, util, Result,
+};
 use cpal::StreamConfig;
 use ringbuf::{Consumer, Producer, RingBuffer};
-use std::sync::mpsc::{self, Receiver, Sender};
+use std::{
+    f32::consts::PI,
+    sync::mpsc::{self, Receiver, Sender},
+};
 use Message::*;
 
 pub type DelayMs = u32;
@@ -9,11 +15,61 @@ pub type DelayMs = u32;
 #[derive(Debug)]
 pub enum Message {
     SetDelay(DelayMs),
+    /// Plays the input backwards, in windows as long as the delay.
+    SetReverse(bool),
+}
+
+/// Plays its input backwards in windows. Two read heads move backwards through a delay line, half
+/// a window apart, and each fades out as it jumps back to the present so that the other covers the
+/// jump.
+struct Reverser {
+    window: f32,
+    phase: f32,
+    delay_lines: Vec<DelayLine>,
+}
+
+impl Reverser {
+    fn new(channels: usize, window: f32, max_window: usize) -> Self {
+        Self {
+            window,
+            phase: 0.0,
+            // a head reaches twice the window back, as it reads backwards while the input moves on
+            delay_lines: (0..channels)
+                .map(|_| DelayLine::new(max_window * 2))
+                .collect(),
+        }
+    }
+
+    fn process(&mut self, input: &[f32], output: &mut [f32]) {
+        let channels = self.delay_lines.len();
+        let max_delay = self.window * 2.0;
+        let phase_increment = 1.0 / self.window.max(1.0);
+
+        for (input, output) in input.chunks(channels).zip(output.chunks_mut(channels)) {
+            let heads = [self.phase, (self.phase + 0.5).fract()];
+
+            for (channel, delay_line) in self.delay_lines.iter_mut().enumerate() {
+                delay_line.write(input[channel]);
+
+                output[channel] = heads
+                    .iter()
+                    .map(|head| delay_line.read(head * max_delay) * (PI * head).sin().powi(2))
+                    .sum();
+            }
+
+            self.phase = (self.phase + phase_increment).fract();
+        }
+    }
 }
 
 pub struct Delay {
     delay_ms: DelayMs,
     stream_config: StreamConfig,
+    reverse: bool,
+    /// Only built when the delay can be reversed, since it needs its own delay line.
+    reverser: Option<Reverser>,
+    // the reverser's output, kept to avoid allocating on every buffer
+    reversed: Vec<f32>,
     messages: Receiver<Message>,
     producer: Producer<f32>,
     consumer: Consumer<f32>,
@@ -24,12 +80,21 @@ impl Delay {
         stream_config: &StreamConfig,
         delay_ms: DelayMs,
         max_delay_ms: DelayMs,
+        can_reverse: bool,
     ) -> Result<(Self, Sender<Message>)> {
         let (sender, receiver) = mpsc::channel();
 
         // size the ring buffer so that it can accomodate the largest allowed delay
         let ring = RingBuffer::new(util::ms_in_samples(stream_config, max_delay_ms) * 2);
         let (mut producer, consumer) = ring.split();
+        let channels = stream_config.channels as usize;
+        let reverser = can_reverse.then(|| {
+            Reverser::new(
+                channels,
+                Self::frames(stream_config, delay_ms),
+                util::ms_in_samples(stream_config, max_delay_ms) / channels,
+            )
+        });
 
         ring_buffer::write_empty_samples(
             &mut producer,
@@ -40,6 +105,9 @@ impl Delay {
             Self {
                 delay_ms,
                 stream_config: stream_config.clone(),
+                reverse: false,
+                reverser,
+                reversed: vec![],
                 messages: receiver,
                 producer,
                 consumer,
@@ -60,6 +128,7 @@ impl Delay {
     fn process_message(&mut self, message: Message) -> Result<()> {
         match message {
             SetDelay(delay) => self.set_delay_ms(delay)?,
+            SetReverse(reverse) => self.reverse = reverse,
         };
 
         Ok(())
@@ -79,9 +148,16 @@ impl Delay {
         }
 
         self.delay_ms = delay_ms;
+        if let Some(reverser) = &mut self.reverser {
+            reverser.window = Self::frames(&self.stream_config, delay_ms);
+        }
 
         Ok(())
     }
+
+    fn frames(stream_config: &StreamConfig, delay_ms: DelayMs) -> f32 {
+        (util::ms_in_samples(stream_config, delay_ms) / stream_config.channels as usize) as f32
+    }
 }
 
 impl AudioUnit for Delay {
@@ -92,6 +168,16 @@ impl AudioUnit for Delay {
         let samples: Vec<f32> = ring_buffer::read_samples(&mut self.consumer, output.len())?;
         output.copy_from_slice(&samples);
 
+        if let Some(reverser) = &mut self.reverser {
+            // keep the reverser running while it isn't heard, so it has history when it's turned on
+            self.reversed.resize(output.len(), 0.0);
+            reverser.process(input, &mut self.reversed);
+
+            if self.reverse {
+                output.copy_from_slice(&self.reversed);
+            }
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,62 @@
+/// A circular buffer for a single channel which can be read at fractional delays, for effects
+/// where the delay time moves continuously.
+#[derive(Debug)]
+pub struct DelayLine {
+    buffer: Vec<f32>,
+    position: usize,
+}
+
+impl DelayLine {
+    pub fn new(max_delay_samples: usize) -> Self {
+        Self {
+            // one extra sample, so that the maximum delay can be interpolated
+            buffer: vec![0.0; max_delay_samples + 2],
+            position: 0,
+        }
+    }
+
+    pub fn write(&mut self, sample: f32) {
+        self.position = (self.position + 1) % self.buffer.len();
+        self.buffer[self.position] = sample;
+    }
+
+    /// Reads the sample written `delay` samples ago, linearly interpolating between neighbouring
+    /// samples. A delay of 0 returns the most recently written sample.
+    pub fn read(&self, delay: f32) -> f32 {
+        let length = self.buffer.len();
+        let delay = delay.clamp(0.0, (length - 2) as f32);
+
+        let whole = delay.floor() as usize;
+        let fraction = delay - whole as f32;
+
+        let newer = self.buffer[(self.position + length - whole) % length];
+        let older = self.buffer[(self.position + length - whole - 1) % length];
+
+        newer + fraction * (older - newer)
+    }
+
+    pub fn clear(&mut self) {
+        for sample in &mut self.buffer {
+            *sample = 0.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read() {
+        let mut delay_line = DelayLine::new(4);
+
+        for sample in [1.0, 2.0, 3.0, 4.0] {
+            delay_line.write(sample);
+        }
+
+        assert_eq!(delay_line.read(0.0), 4.0);
+        assert_eq!(delay_line.read(1.0), 3.0);
+        assert_eq!(delay_line.read(3.0), 1.0);
+        assert_eq!(delay_line.read(1.5), 2.5);
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{
+    audio_unit::{AudioUnit, Oversampler},
+    Result,
+};
+use cpal::StreamConfig;
+use serde::Deserialize;
+use std::sync::mpsc::{self, Receiver, Sender};
+use Message::*;
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub enum Shape {
+    SoftClip,
+    HardClip,
+    Tube,
+    Fuzz,
+}
+
+impl Shape {
+    // offsets the transfer curve so that positive and negative halves clip differently
+    const TUBE_BIAS: f32 = 0.2;
+    const FUZZ_BIAS: f32 = 0.3;
+
+    pub fn apply(self, sample: f32) -> f32 {
+        match self {
+            Shape::SoftClip => sample.tanh(),
+            Shape::HardClip => sample.clamp(-1.0, 1.0),
+            Shape::Tube => (sample + Self::TUBE_BIAS).tanh() - Self::TUBE_BIAS.tanh(),
+            Shape::Fuzz => {
+                let biased = sample + Self::FUZZ_BIAS;
+                let clipped = (1.0 - (-biased.abs() * 2.0).exp()).copysign(biased);
+                clipped - (1.0 - (-Self::FUZZ_BIAS * 2.0).exp())
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Message {
+    SetDrive(f32),
+    SetLevel(f32),
+}
+
+pub struct Drive {
+    shape: Shape,
+    drive: f32,
+    level: f32,
+    oversampler: Oversampler,
+    messages: Receiver<Message>,
+}
+
+impl Drive {
+    pub fn new(
+        stream_config: &StreamConfig,
+        shape: Shape,
+        drive: f32,
+        level: f32,
+        oversampling: usize,
+    ) -> (Self, Sender<Message>) {
+        let (sender, receiver) = mpsc::channel();
+
+        (
+            Self {
+                shape,
+                drive,
+                level,
+                oversampler: Oversampler::new(oversampling, stream_config.channels as usize),
+                messages: receiver,
+            },
+            sender,
+        )
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        for message in messages {
+            match message {
+                SetDrive(drive) => self.drive = drive,
+                SetLevel(level) => self.level = level,
+            }
+        }
+    }
+}
+
+impl AudioUnit for Drive {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_messages();
+
+        let shape = self.shape;
+        let drive = self.drive;
+        let level = self.level;
+
+        self.oversampler
+            .process(input, output, |sample| shape.apply(sample * drive) * level);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+/// Tracks the level of a signal, rising at the attack rate and falling at the release rate.
+#[derive(Debug)]
+pub struct EnvelopeFollower {
+    attack: f32,
+    release: f32,
+    envelope: f32,
+}
+
+impl EnvelopeFollower {
+    pub fn new(sample_rate: f32, attack_ms: f32, release_ms: f32) -> Self {
+        Self {
+            attack: Self::coefficient(sample_rate, attack_ms),
+            release: Self::coefficient(sample_rate, release_ms),
+            envelope: 0.0,
+        }
+    }
+
+    /// The per-sample smoothing coefficient for a time constant. A time of zero responds
+    /// instantly.
+    fn coefficient(sample_rate: f32, ms: f32) -> f32 {
+        if ms <= 0.0 {
+            0.0
+        } else {
+            (-1.0 / (ms / 1_000.0 * sample_rate)).exp()
+        }
+    }
+
+    pub fn set_times(&mut self, sample_rate: f32, attack_ms: f32, release_ms: f32) {
+        self.attack = Self::coefficient(sample_rate, attack_ms);
+        self.release = Self::coefficient(sample_rate, release_ms);
+    }
+
+    /// Jumps straight to `envelope`, without attacking or releasing.
+    pub fn reset(&mut self, envelope: f32) {
+        self.envelope = envelope;
+    }
+
+    pub fn envelope(&self) -> f32 {
+        self.envelope
+    }
+
+    pub fn process(&mut self, level: f32) -> f32 {
+        let coefficient = if level > self.envelope {
+            self.attack
+        } else {
+            self.release
+        };
+
+        self.envelope = level + coefficient * (self.envelope - level);
+        self.envelope
+    }
+
+    /// Follows the peak level of an interleaved frame, so that all channels are treated alike.
+    pub fn process_frame(&mut self, frame: &[f32]) -> f32 {
+        let peak = frame
+            .iter()
+            .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+        self.process(peak)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instant_attack_and_release() {
+        let mut follower = EnvelopeFollower::new(48_000.0, 0.0, 0.0);
+
+        assert_eq!(follower.process(0.5), 0.5);
+        assert_eq!(follower.process(0.25), 0.25);
+    }
+
+    #[test]
+    fn test_attack_reaches_level_after_time_constant() {
+        let sample_rate = 1_000.0;
+        let mut follower = EnvelopeFollower::new(sample_rate, 10.0, 100.0);
+
+        for _ in 0..10 {
+            follower.process(1.0);
+        }
+
+        // after one time constant, an exponential envelope has covered ~63% of the distance
+        assert!((follower.envelope() - 0.632).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_release_is_slower_than_attack() {
+        let mut follower = EnvelopeFollower::new(1_000.0, 1.0, 100.0);
+
+        for _ in 0..100 {
+            follower.process(1.0);
+        }
+
+        follower.process(0.0);
+        assert!(follower.envelope() > 0.9);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{audio_unit::AudioUnit, util::number, Result};
-use rustfft::FftPlanner;
+use rustfft::{num_complex::Complex, FftPlanner};
 
 pub struct Fft {
     planner: FftPlanner<f32>,
@@ -11,27 +11,35 @@ impl Fft {
             planner: FftPlanner::new(),
         }
     }
+
+    /// Transforms `buffer` from the time domain into the frequency domain, in place.
+    pub fn forward(&mut self, buffer: &mut [Complex<f32>]) {
+        self.planner.plan_fft_forward(buffer.len()).process(buffer);
+    }
+
+    /// Transforms `buffer` from the frequency domain back into the time domain, in place. The
+    /// result is scaled so that `forward` followed by `inverse` returns the original values.
+    pub fn inverse(&mut self, buffer: &mut [Complex<f32>]) {
+        let length = buffer.len();
+        self.planner.plan_fft_inverse(length).process(buffer);
+
+        let scale = length as f32;
+        for value in buffer {
+            *value /= scale;
+        }
+    }
 }
 
 impl AudioUnit for Fft {
     fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
         let mut input_as_complex: Vec<_> = input.iter().copied().map(number::to_complex).collect();
 
-        let length = input.len();
-
-        self.planner
-            .plan_fft_forward(length)
-            .process(&mut input_as_complex);
-
-        self.planner
-            .plan_fft_inverse(length)
-            .process(&mut input_as_complex);
-
-        let scale = length as f32;
+        self.forward(&mut input_as_complex);
+        self.inverse(&mut input_as_complex);
 
-        for i in 0..length {
+        for (i, value) in input_as_complex.iter().enumerate() {
             // the imaginary part of the number is ~0, so it can be discarded
-            output[i] = input_as_complex[i].re / scale;
+            output[i] = value.re;
         }
 
         Ok(())
@@ -0,0 +1,270 @@
+use crate::{
+    audio_unit::{AudioUnit, Fft},
+    util::{log, random::Random},
+    Result,
+};
+use cpal::StreamConfig;
+use rustfft::num_complex::Complex;
+use std::{
+    f32::consts::PI,
+    sync::mpsc::{self, Receiver, Sender},
+};
+use Message::*;
+
+#[derive(Debug)]
+pub enum Message {
+    /// Captures the spectrum at the next analysis frame, and starts sustaining it.
+    Freeze,
+    /// Fades the sustained spectrum out.
+    Release,
+}
+
+#[derive(Debug, PartialEq)]
+enum State {
+    Off,
+    Capturing,
+    Frozen,
+    Releasing,
+}
+
+struct Channel {
+    // the most recent `frame_size` input samples, as a ring
+    input: Vec<f32>,
+    // overlap-added output which hasn't been played yet, as a ring
+    output: Vec<f32>,
+    magnitudes: Vec<f32>,
+}
+
+/// Sustains a snapshot of the input's spectrum indefinitely. Each hop resynthesizes the captured
+/// magnitudes with random phases, and the overlapping frames blend into a smooth drone.
+pub struct Freeze {
+    fft: Fft,
+    random: Random,
+    frame_size: usize,
+    hop_size: usize,
+    window: Vec<f32>,
+    /// How much the gain changes each frame as it fades in and out.
+    gain_step: f32,
+    gain: f32,
+    state: State,
+    position: usize,
+    samples_until_hop: usize,
+    channels: Vec<Channel>,
+    messages: Receiver<Message>,
+}
+
+impl Freeze {
+    const OVERLAP: usize = 4;
+
+    pub fn new(
+        stream_config: &StreamConfig,
+        frame_size: usize,
+        release_ms: u32,
+    ) -> (Self, Sender<Message>) {
+        let (sender, receiver) = mpsc::channel();
+        let hop_size = frame_size / Self::OVERLAP;
+
+        let window = (0..frame_size)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / frame_size as f32).cos())
+            .collect();
+
+        let channels = (0..stream_config.channels)
+            .map(|_| Channel {
+                input: vec![0.0; frame_size],
+                output: vec![0.0; frame_size],
+                magnitudes: vec![0.0; frame_size / 2 + 1],
+            })
+            .collect();
+
+        (
+            Self {
+                fft: Fft::new(),
+                random: Random::from_time(),
+                frame_size,
+                hop_size,
+                window,
+                gain_step: 1.0
+                    / (release_ms as f32 / 1_000.0 * stream_config.sample_rate.0 as f32).max(1.0),
+                gain: 0.0,
+                state: State::Off,
+                position: 0,
+                samples_until_hop: hop_size,
+                channels,
+                messages: receiver,
+            },
+            sender,
+        )
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        for message in messages {
+            match message {
+                Freeze => {
+                    log::print("freeze: capturing");
+                    // nothing is playing yet, so the first frame's window fades it in. Otherwise
+                    // the gain ramps back up from where the release had got to, and the old tail
+                    // fades out under the new frames
+                    if self.state == State::Off {
+                        self.gain = 1.0;
+                    }
+                    self.state = State::Capturing;
+                }
+                Release => {
+                    if self.state != State::Off {
+                        log::print("freeze: releasing");
+                        self.state = State::Releasing;
+                    }
+                }
+            }
+        }
+    }
+
+    fn capture(&mut self, channel: usize) {
+        let frame_size = self.frame_size;
+        let input = &self.channels[channel].input;
+
+        // unroll the ring so that the oldest sample comes first
+        let mut frame: Vec<Complex<f32>> = (0..frame_size)
+            .map(|i| {
+                let sample = input[(self.position + i) % frame_size];
+                Complex::new(sample * self.window[i], 0.0)
+            })
+            .collect();
+
+        self.fft.forward(&mut frame);
+
+        for (bin, magnitude) in self.channels[channel].magnitudes.iter_mut().enumerate() {
+            *magnitude = frame[bin].norm();
+        }
+    }
+
+    fn synthesize(&mut self, channel: usize) {
+        let frame_size = self.frame_size;
+        let mut frame = vec![Complex::new(0.0, 0.0); frame_size];
+
+        for (bin, magnitude) in self.channels[channel].magnitudes.iter().enumerate() {
+            let phase = self.random.next_f32() * 2.0 * PI;
+            frame[bin] = Complex::from_polar(*magnitude, phase);
+
+            // mirror the spectrum, so that the inverse transform is real
+            if bin > 0 && bin < frame_size - bin {
+                frame[frame_size - bin] = frame[bin].conj();
+            }
+        }
+
+        self.fft.inverse(&mut frame);
+
+        // hann analysis and synthesis windows overlapped by four sum to 1.5
+        let scale = 1.0 / 1.5;
+        let output = &mut self.channels[channel].output;
+
+        for (i, value) in frame.iter().enumerate() {
+            output[(self.position + i) % frame_size] += value.re * self.window[i] * scale;
+        }
+    }
+
+    fn hop(&mut self) {
+        for channel in 0..self.channels.len() {
+            if self.state == State::Capturing {
+                self.capture(channel);
+            }
+
+            if self.state != State::Off {
+                self.synthesize(channel);
+            }
+        }
+
+        if self.state == State::Capturing {
+            self.state = State::Frozen;
+        }
+    }
+
+    /// The gain applied to the output, which ramps down while releasing, and back up if the
+    /// spectrum is frozen again before the release finishes.
+    fn next_gain(&mut self) -> f32 {
+        match self.state {
+            State::Off => (),
+            State::Releasing => {
+                self.gain -= self.gain_step;
+
+                if self.gain <= 0.0 {
+                    log::print("freeze: off");
+                    self.state = State::Off;
+                    self.gain = 0.0;
+                    for channel in &mut self.channels {
+                        channel.output.iter_mut().for_each(|sample| *sample = 0.0);
+                    }
+                }
+            }
+            State::Capturing | State::Frozen => {
+                self.gain = (self.gain + self.gain_step).min(1.0);
+            }
+        }
+
+        self.gain
+    }
+}
+
+impl AudioUnit for Freeze {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_messages();
+
+        let num_channels = self.channels.len();
+
+        for (input, output) in input
+            .chunks(num_channels)
+            .zip(output.chunks_mut(num_channels))
+        {
+            let gain = self.next_gain();
+
+            for (i, channel) in self.channels.iter_mut().enumerate() {
+                channel.input[self.position] = input[i];
+                output[i] = channel.output[self.position] * gain;
+                channel.output[self.position] = 0.0;
+            }
+
+            self.position = (self.position + 1) % self.frame_size;
+            self.samples_until_hop -= 1;
+
+            if self.samples_until_hop == 0 {
+                self.samples_until_hop = self.hop_size;
+                self.hop();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cpal::{BufferSize, SampleRate};
+
+    #[test]
+    fn test_freezing_during_release_ramps_back_up() {
+        let stream_config = StreamConfig {
+            channels: 1,
+            sample_rate: SampleRate(1_000),
+            buffer_size: BufferSize::Default,
+        };
+        let (mut freeze, messages) = Freeze::new(&stream_config, 64, 10);
+
+        messages.send(Message::Freeze).unwrap();
+        freeze.process_messages();
+        assert_eq!(freeze.next_gain(), 1.0);
+
+        messages.send(Message::Release).unwrap();
+        freeze.process_messages();
+        for _ in 0..5 {
+            freeze.next_gain();
+        }
+        assert!((freeze.gain - 0.5).abs() < 1e-6);
+
+        // the gain carries on from where the release got to, rather than jumping back to 1
+        messages.send(Message::Freeze).unwrap();
+        freeze.process_messages();
+        assert!((freeze.next_gain() - 0.6).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{
+    audio_unit::{AudioUnit, EnvelopeFollower},
+    util, Result,
+};
+use cpal::StreamConfig;
+use std::sync::mpsc::{self, Receiver, Sender};
+use Message::*;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Parameters {
+    pub open_threshold_db: f32,
+    pub close_threshold_db: f32,
+    pub hold_ms: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+    pub range_db: f32,
+}
+
+#[derive(Debug)]
+pub enum Message {
+    /// Moves both thresholds, keeping the hysteresis between them.
+    SetThreshold(f32),
+}
+
+pub struct Gate {
+    parameters: Parameters,
+    sample_rate: f32,
+    channels: usize,
+    detector: EnvelopeFollower,
+    gain: EnvelopeFollower,
+    is_open: bool,
+    hold_remaining: usize,
+    messages: Receiver<Message>,
+}
+
+impl Gate {
+    const DETECTOR_ATTACK_MS: f32 = 0.1;
+    const DETECTOR_RELEASE_MS: f32 = 20.0;
+
+    pub fn new(stream_config: &StreamConfig, parameters: Parameters) -> (Self, Sender<Message>) {
+        let (sender, receiver) = mpsc::channel();
+        let sample_rate = stream_config.sample_rate.0 as f32;
+
+        (
+            Self {
+                parameters,
+                sample_rate,
+                channels: stream_config.channels as usize,
+                detector: EnvelopeFollower::new(
+                    sample_rate,
+                    Self::DETECTOR_ATTACK_MS,
+                    Self::DETECTOR_RELEASE_MS,
+                ),
+                gain: EnvelopeFollower::new(
+                    sample_rate,
+                    parameters.attack_ms,
+                    parameters.release_ms,
+                ),
+                is_open: false,
+                hold_remaining: 0,
+                messages: receiver,
+            },
+            sender,
+        )
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        for message in messages {
+            match message {
+                SetThreshold(threshold_db) => {
+                    let hysteresis =
+                        self.parameters.open_threshold_db - self.parameters.close_threshold_db;
+                    self.parameters.open_threshold_db = threshold_db;
+                    self.parameters.close_threshold_db = threshold_db - hysteresis;
+                }
+            }
+        }
+    }
+
+    fn update_is_open(&mut self, level_db: f32) {
+        if level_db >= self.parameters.open_threshold_db {
+            self.is_open = true;
+            self.hold_remaining = (self.parameters.hold_ms / 1_000.0 * self.sample_rate) as usize;
+        } else if level_db < self.parameters.close_threshold_db {
+            if self.hold_remaining > 0 {
+                self.hold_remaining -= 1;
+            } else {
+                self.is_open = false;
+            }
+        }
+    }
+
+    /// Gates `input` based on the level of `sidechain`, which must be the same length.
+    pub fn process_with_sidechain(
+        &mut self,
+        sidechain: &[f32],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        self.process_messages();
+
+        let closed_gain = util::db_to_gain(self.parameters.range_db);
+
+        for ((input, sidechain), output) in input
+            .chunks(self.channels)
+            .zip(sidechain.chunks(self.channels))
+            .zip(output.chunks_mut(self.channels))
+        {
+            let level_db = util::gain_to_db(self.detector.process_frame(sidechain));
+            self.update_is_open(level_db);
+
+            let target = if self.is_open { 1.0 } else { closed_gain };
+            let gain = self.gain.process(target);
+
+            for (i, sample) in input.iter().enumerate() {
+                output[i] = sample * gain;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl AudioUnit for Gate {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_with_sidechain(input, input, output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cpal::{BufferSize, SampleRate};
+
+    fn gate() -> Gate {
+        let stream_config = StreamConfig {
+            channels: 1,
+            sample_rate: SampleRate(1_000),
+            buffer_size: BufferSize::Default,
+        };
+        let parameters = Parameters {
+            open_threshold_db: -20.0,
+            close_threshold_db: -30.0,
+            hold_ms: 3.0,
+            attack_ms: 0.0,
+            release_ms: 0.0,
+            range_db: -60.0,
+        };
+
+        Gate::new(&stream_config, parameters).0
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        let mut gate = gate();
+
+        gate.update_is_open(-25.0);
+        assert!(!gate.is_open);
+        gate.update_is_open(-10.0);
+        assert!(gate.is_open);
+        // between the thresholds, the gate stays as it was
+        gate.update_is_open(-25.0);
+        assert!(gate.is_open);
+    }
+
+    #[test]
+    fn test_holds_before_closing() {
+        let mut gate = gate();
+        gate.update_is_open(-10.0);
+
+        // 3ms is 3 frames
+        for _ in 0..3 {
+            gate.update_is_open(-40.0);
+            assert!(gate.is_open);
+        }
+        gate.update_is_open(-40.0);
+        assert!(!gate.is_open);
+
+        gate.update_is_open(-25.0);
+        assert!(!gate.is_open);
+    }
+
+    #[test]
+    fn test_closed_gate_attenuates_by_the_range() {
+        let mut gate = gate();
+        let mut output = vec![0.0; 4];
+
+        gate.process(&[0.01; 4], &mut output).unwrap();
+        for sample in output {
+            assert!((sample - 0.01 * util::db_to_gain(-60.0)).abs() < 1e-9);
+        }
+    }
+}
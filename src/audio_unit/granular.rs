@@ -0,0 +1,228 @@
+use crate::{
+    audio_unit::{pan, AudioUnit, DelayLine},
+    util::random::Random,
+    Result,
+};
+use cpal::StreamConfig;
+use std::{
+    f32::consts::PI,
+    sync::mpsc::{self, Receiver, Sender},
+};
+use Message::*;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Parameters {
+    /// The number of grains started per second.
+    pub density: f32,
+    /// The length of each grain.
+    pub size_ms: f32,
+    /// How far back in the recording grains can start, chosen at random for each grain.
+    pub position_ms: f32,
+    /// The pitch of the grains, in semitones.
+    pub pitch: f32,
+    /// How far each grain's pitch can stray from `pitch` at random, in semitones.
+    pub pitch_spread: f32,
+    /// How far each grain can be panned from the center at random, from 0 to 1.
+    pub stereo_spread: f32,
+    /// How much of the cloud is recorded back into the buffer.
+    pub feedback: f32,
+    pub mix: f32,
+}
+
+#[derive(Debug)]
+pub enum Message {
+    SetDensity(f32),
+    SetSize(f32),
+    SetPosition(f32),
+    SetPitch(f32),
+    SetPitchSpread(f32),
+    SetFeedback(f32),
+    SetMix(f32),
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct Grain {
+    is_active: bool,
+    /// How far behind the write position the grain is reading, in samples.
+    delay: f32,
+    /// How much the delay changes each sample. Grains played faster than they were recorded
+    /// catch up with the write position, and slower ones fall behind.
+    delay_increment: f32,
+    length: usize,
+    age: usize,
+    left: f32,
+    right: f32,
+}
+
+impl Grain {
+    /// A Hann window, so that the grain fades in and out without clicking.
+    fn envelope(&self) -> f32 {
+        (PI * self.age as f32 / self.length as f32).sin().powi(2)
+    }
+}
+
+/// Records into a circular buffer, and plays back many short, overlapping grains from random
+/// points in the recording, with random pitches and positions in the stereo field.
+pub struct Granular {
+    parameters: Parameters,
+    samples_per_ms: f32,
+    sample_rate: f32,
+    random: Random,
+    grains: Vec<Grain>,
+    samples_until_grain: usize,
+    delay_lines: Vec<DelayLine>,
+    max_delay: f32,
+    cloud: Vec<f32>,
+    messages: Receiver<Message>,
+}
+
+impl Granular {
+    const MAX_GRAINS: usize = 64;
+
+    pub fn new(
+        stream_config: &StreamConfig,
+        parameters: Parameters,
+        max_delay_ms: f32,
+    ) -> (Self, Sender<Message>) {
+        let (sender, receiver) = mpsc::channel();
+        let sample_rate = stream_config.sample_rate.0 as f32;
+        let samples_per_ms = sample_rate / 1_000.0;
+        let max_delay = max_delay_ms * samples_per_ms;
+        let channels = stream_config.channels as usize;
+
+        (
+            Self {
+                parameters,
+                samples_per_ms,
+                sample_rate,
+                random: Random::from_time(),
+                grains: vec![Grain::default(); Self::MAX_GRAINS],
+                samples_until_grain: 0,
+                delay_lines: (0..channels)
+                    .map(|_| DelayLine::new(max_delay.ceil() as usize))
+                    .collect(),
+                max_delay,
+                cloud: vec![0.0; channels],
+                messages: receiver,
+            },
+            sender,
+        )
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        for message in messages {
+            match message {
+                SetDensity(density) => self.parameters.density = density,
+                SetSize(size_ms) => self.parameters.size_ms = size_ms,
+                SetPosition(position_ms) => self.parameters.position_ms = position_ms,
+                SetPitch(pitch) => self.parameters.pitch = pitch,
+                SetPitchSpread(pitch_spread) => self.parameters.pitch_spread = pitch_spread,
+                SetFeedback(feedback) => self.parameters.feedback = feedback,
+                SetMix(mix) => self.parameters.mix = mix,
+            }
+        }
+    }
+
+    fn start_grain(&mut self) {
+        let Parameters {
+            size_ms,
+            position_ms,
+            pitch,
+            pitch_spread,
+            stereo_spread,
+            ..
+        } = self.parameters;
+
+        let length = (size_ms * self.samples_per_ms).max(1.0) as usize;
+        let semitones = pitch + pitch_spread * self.random.next_bipolar();
+        let delay_increment = 1.0 - 2.0_f32.powf(semitones / 12.0);
+
+        // leave room for grains which catch up with the write position, and for those which fall
+        // behind to stay inside the buffer
+        let catch_up = (-delay_increment).max(0.0) * length as f32;
+        let fall_behind = delay_increment.max(0.0) * length as f32;
+        let latest = catch_up + 1.0;
+        let earliest =
+            (latest + position_ms * self.samples_per_ms).min(self.max_delay - fall_behind);
+        let delay = latest + (earliest - latest).max(0.0) * self.random.next_f32();
+
+        let (left, right) = pan::gains(stereo_spread * self.random.next_bipolar());
+
+        if let Some(grain) = self.grains.iter_mut().find(|grain| !grain.is_active) {
+            *grain = Grain {
+                is_active: true,
+                delay,
+                delay_increment,
+                length,
+                age: 0,
+                left,
+                right,
+            };
+        }
+    }
+
+    /// The time until the next grain, jittered so that the grains don't fall into a rhythm.
+    fn next_grain_interval(&mut self) -> usize {
+        let average = self.sample_rate / self.parameters.density.max(0.1);
+        (average * (0.5 + self.random.next_f32())).max(1.0) as usize
+    }
+
+    /// Scales the cloud so that its level stays roughly the same as the density and size change.
+    /// Grains from different points in the recording are uncorrelated, so they add up by power.
+    fn normalization(&self) -> f32 {
+        let overlap = self.parameters.density * self.parameters.size_ms / 1_000.0 * 0.5;
+        1.0 / overlap.max(1.0).sqrt()
+    }
+
+    fn read_grains(&mut self) {
+        let normalization = self.normalization();
+        let channels = self.cloud.len();
+        self.cloud.iter_mut().for_each(|sample| *sample = 0.0);
+
+        for grain in self.grains.iter_mut().filter(|grain| grain.is_active) {
+            let envelope = grain.envelope() * normalization;
+
+            for (channel, delay_line) in self.delay_lines.iter().enumerate() {
+                let gain = match (channels, channel) {
+                    (1, _) => 1.0,
+                    (_, 0) => grain.left,
+                    (_, 1) => grain.right,
+                    _ => 1.0,
+                };
+                self.cloud[channel] += delay_line.read(grain.delay) * envelope * gain;
+            }
+
+            grain.delay += grain.delay_increment;
+            grain.age += 1;
+            grain.is_active = grain.age < grain.length;
+        }
+    }
+}
+
+impl AudioUnit for Granular {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_messages();
+
+        let channels = self.delay_lines.len();
+        let Parameters { feedback, mix, .. } = self.parameters;
+
+        for (input, output) in input.chunks(channels).zip(output.chunks_mut(channels)) {
+            if self.samples_until_grain == 0 {
+                self.start_grain();
+                self.samples_until_grain = self.next_grain_interval();
+            }
+            self.samples_until_grain -= 1;
+
+            self.read_grains();
+
+            for channel in 0..channels {
+                let cloud = self.cloud[channel];
+                self.delay_lines[channel].write(input[channel] + cloud * feedback);
+                output[channel] = input[channel] * (1.0 - mix) + cloud * mix;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use serde::Deserialize;
+use std::f32::consts::PI;
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub enum Shape {
+    Sine,
+    Triangle,
+    Square,
+    SawUp,
+    SawDown,
+}
+
+impl Shape {
+    /// The value of the waveform, between -1 and 1, at a phase between 0 and 1.
+    pub fn value_at(self, phase: f32) -> f32 {
+        match self {
+            Shape::Sine => (2.0 * PI * phase).sin(),
+            Shape::Triangle => 1.0 - 4.0 * ((phase + 0.25).fract() - 0.5).abs(),
+            Shape::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Shape::SawUp => 2.0 * phase - 1.0,
+            Shape::SawDown => 1.0 - 2.0 * phase,
+        }
+    }
+}
+
+/// A low frequency oscillator, for modulating parameters over time. It is advanced once per
+/// frame, so that every channel in the frame sees the same phase.
+#[derive(Debug)]
+pub struct Lfo {
+    shape: Shape,
+    frequency: f32,
+    sample_rate: f32,
+    phase: f32,
+}
+
+impl Lfo {
+    pub fn new(sample_rate: f32, shape: Shape, frequency: f32) -> Self {
+        Self {
+            shape,
+            frequency,
+            sample_rate,
+            phase: 0.0,
+        }
+    }
+
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.frequency = frequency;
+    }
+
+    /// Restarts the cycle, eg. to line it up with a tapped beat.
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    /// The current value, with the phase shifted by `offset` cycles.
+    pub fn value_with_offset(&self, offset: f32) -> f32 {
+        self.shape.value_at((self.phase + offset).fract())
+    }
+
+    pub fn value(&self) -> f32 {
+        self.shape.value_at(self.phase)
+    }
+
+    pub fn advance(&mut self) {
+        self.phase = (self.phase + self.frequency / self.sample_rate).fract();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-5,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_shapes() {
+        for (phase, sine, triangle) in [
+            (0.0, 0.0, 0.0),
+            (0.25, 1.0, 1.0),
+            (0.5, 0.0, 0.0),
+            (0.75, -1.0, -1.0),
+        ] {
+            assert_close(Shape::Sine.value_at(phase), sine);
+            assert_close(Shape::Triangle.value_at(phase), triangle);
+        }
+
+        assert_close(Shape::SawUp.value_at(0.0), -1.0);
+        assert_close(Shape::SawDown.value_at(0.0), 1.0);
+        assert_close(Shape::Square.value_at(0.75), -1.0);
+    }
+
+    #[test]
+    fn test_advance_and_reset() {
+        let mut lfo = Lfo::new(4.0, Shape::Sine, 1.0);
+
+        lfo.advance();
+        assert_close(lfo.value(), 1.0);
+
+        lfo.reset();
+        assert_close(lfo.value(), 0.0);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{
+    audio_unit::{AudioUnit, EnvelopeFollower},
+    util, Result,
+};
+use cpal::StreamConfig;
+use std::{
+    collections::VecDeque,
+    sync::mpsc::{self, Receiver, Sender},
+};
+use Message::*;
+
+#[derive(Debug)]
+pub enum Message {
+    SetCeiling(f32),
+}
+
+/// A lookahead limiter. The input is delayed by the lookahead time, so that gain reduction can
+/// ramp in before a peak arrives instead of clipping it.
+pub struct Limiter {
+    ceiling: f32,
+    channels: usize,
+    lookahead_frames: usize,
+    delayed: VecDeque<f32>,
+    required_gains: VecDeque<f32>,
+    reduction: EnvelopeFollower,
+    messages: Receiver<Message>,
+}
+
+impl Limiter {
+    pub fn new(
+        stream_config: &StreamConfig,
+        ceiling_db: f32,
+        lookahead_ms: f32,
+        release_ms: f32,
+    ) -> (Self, Sender<Message>) {
+        let (sender, receiver) = mpsc::channel();
+        let sample_rate = stream_config.sample_rate.0 as f32;
+        let channels = stream_config.channels as usize;
+        let lookahead_frames = ((lookahead_ms / 1_000.0 * sample_rate) as usize).max(1);
+
+        (
+            Self {
+                ceiling: util::db_to_gain(ceiling_db),
+                channels,
+                lookahead_frames,
+                delayed: vec![0.0; lookahead_frames * channels].into(),
+                // covers every frame from the one being output to the one just received
+                required_gains: vec![1.0; lookahead_frames + 1].into(),
+                // attacking over a third of the lookahead gets within 5% of the target in time
+                reduction: EnvelopeFollower::new(sample_rate, lookahead_ms / 3.0, release_ms),
+                messages: receiver,
+            },
+            sender,
+        )
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        for message in messages {
+            match message {
+                SetCeiling(ceiling_db) => self.ceiling = util::db_to_gain(ceiling_db),
+            }
+        }
+    }
+}
+
+impl AudioUnit for Limiter {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_messages();
+
+        for (input, output) in input
+            .chunks(self.channels)
+            .zip(output.chunks_mut(self.channels))
+        {
+            let peak = input
+                .iter()
+                .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+            let required_gain = if peak > self.ceiling {
+                self.ceiling / peak
+            } else {
+                1.0
+            };
+
+            self.required_gains.push_back(required_gain);
+            self.required_gains.pop_front();
+            self.delayed.extend(input);
+
+            let target = self
+                .required_gains
+                .iter()
+                .fold(1.0_f32, |gain, required| gain.min(*required));
+            let gain = 1.0 - self.reduction.process(1.0 - target);
+
+            for output in output.iter_mut() {
+                let sample = self.delayed.pop_front().unwrap_or(0.0) * gain;
+                // the envelope can lag behind very fast transients, so guarantee the ceiling
+                *output = sample.clamp(-self.ceiling, self.ceiling);
+            }
+        }
+
+        debug_assert_eq!(self.delayed.len(), self.lookahead_frames * self.channels);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cpal::{BufferSize, SampleRate};
+    use std::f32::consts::PI;
+
+    const LOOKAHEAD_FRAMES: usize = 5;
+
+    fn limiter() -> Limiter {
+        let stream_config = StreamConfig {
+            channels: 1,
+            sample_rate: SampleRate(1_000),
+            buffer_size: BufferSize::Default,
+        };
+
+        Limiter::new(&stream_config, -6.0, LOOKAHEAD_FRAMES as f32, 50.0).0
+    }
+
+    #[test]
+    fn test_quiet_input_is_delayed_by_the_lookahead() {
+        let mut limiter = limiter();
+        let input = (0..20).map(|i| i as f32 / 100.0).collect::<Vec<_>>();
+        let mut output = vec![0.0; 20];
+        limiter.process(&input, &mut output).unwrap();
+
+        assert_eq!(output[..LOOKAHEAD_FRAMES], [0.0; LOOKAHEAD_FRAMES]);
+        assert_eq!(output[LOOKAHEAD_FRAMES..], input[..20 - LOOKAHEAD_FRAMES]);
+    }
+
+    #[test]
+    fn test_output_never_exceeds_the_ceiling() {
+        let mut limiter = limiter();
+        let ceiling = util::db_to_gain(-6.0);
+        // bursts which go well over the ceiling
+        let input = (0..2_000)
+            .map(|i| {
+                let level = if (i / 100) % 2 == 0 { 4.0 } else { 0.1 };
+                level * (2.0 * PI * 50.0 * i as f32 / 1_000.0).sin()
+            })
+            .collect::<Vec<_>>();
+        let mut output = vec![0.0; 2_000];
+        limiter.process(&input, &mut output).unwrap();
+
+        assert!(output.iter().all(|sample| sample.abs() <= ceiling));
+        // the loud bursts are turned down to the ceiling, rather than being let through
+        let peak = output[1_000..1_100]
+            .iter()
+            .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+        assert!(peak > ceiling * 0.9, "peak {}", peak);
+    }
+}
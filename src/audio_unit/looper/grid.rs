@@ -0,0 +1,137 @@
+use serde::Deserialize;
+
+/// When the looper starts and stops recording.
+#[derive(Copy, Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+pub enum Quantize {
+    /// As soon as the note arrives.
+    #[default]
+    Off,
+    /// On the next beat of the tempo.
+    Beat,
+    /// On the first beat of the next bar.
+    Bar,
+}
+
+/// Counts frames through each bar, so that the looper can wait for the next beat or bar.
+pub struct Grid {
+    quantize: Quantize,
+    beats_per_bar: usize,
+    frames_per_beat: Option<usize>,
+    /// The number of frames since the start of the bar.
+    position: usize,
+}
+
+impl Grid {
+    pub fn new(quantize: Quantize, beats_per_bar: usize) -> Self {
+        Self {
+            quantize,
+            beats_per_bar: beats_per_bar.max(1),
+            frames_per_beat: None,
+            position: 0,
+        }
+    }
+
+    pub fn quantize(&self) -> Quantize {
+        self.quantize
+    }
+
+    /// Sets the length of a beat, keeping the same place in the bar. The first bar starts on the
+    /// current frame.
+    pub fn set_beat(&mut self, frames_per_beat: usize) {
+        let frames_per_beat = frames_per_beat.max(1);
+
+        if let Some(old_frames_per_beat) = self.frames_per_beat {
+            self.position = self.position * frames_per_beat / old_frames_per_beat;
+        }
+
+        self.frames_per_beat = Some(frames_per_beat);
+    }
+
+    /// Sets the length of a beat, and starts a new bar on the current frame.
+    pub fn start_bar(&mut self, frames_per_beat: usize) {
+        self.frames_per_beat = Some(frames_per_beat.max(1));
+        self.position = 0;
+    }
+
+    /// Moves to the nearest beat, keeping the same place in the bar, when a beat of the tempo
+    /// starts. Small errors in the length of a beat would otherwise build up into the grid drifting
+    /// away from the tempo.
+    pub fn sync_beat(&mut self) {
+        if let Some(frames_per_beat) = self.frames_per_beat {
+            let bar = frames_per_beat * self.beats_per_bar;
+            let beat = (self.position + frames_per_beat / 2) / frames_per_beat;
+            self.position = beat * frames_per_beat % bar;
+        }
+    }
+
+    /// Whether changes wait for the next beat or bar. They can't until there is a tempo.
+    pub fn is_quantized(&self) -> bool {
+        self.quantize != Quantize::Off && self.frames_per_beat.is_some()
+    }
+
+    /// Whether the current frame starts a beat or bar, depending on `quantize`.
+    pub fn is_on_boundary(&self) -> bool {
+        match (self.quantize, self.frames_per_beat) {
+            (Quantize::Beat, Some(frames_per_beat)) => {
+                self.position.is_multiple_of(frames_per_beat)
+            }
+            (Quantize::Bar, Some(_)) => self.position == 0,
+            _ => true,
+        }
+    }
+
+    /// Moves the grid back by the given number of frames, along with the loops.
+    pub fn rewind(&mut self, frames: usize) {
+        if let Some(frames_per_beat) = self.frames_per_beat {
+            let bar = frames_per_beat * self.beats_per_bar;
+            self.position = (self.position + bar - frames % bar) % bar;
+        }
+    }
+
+    pub fn advance(&mut self) {
+        if let Some(frames_per_beat) = self.frames_per_beat {
+            self.position = (self.position + 1) % (frames_per_beat * self.beats_per_bar);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boundaries(quantize: Quantize) -> Vec<usize> {
+        let mut grid = Grid::new(quantize, 2);
+        grid.set_beat(3);
+
+        (0..12)
+            .filter(|_| {
+                let is_on_boundary = grid.is_on_boundary();
+                grid.advance();
+                is_on_boundary
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_boundaries() {
+        assert_eq!(boundaries(Quantize::Beat), vec![0, 3, 6, 9]);
+        assert_eq!(boundaries(Quantize::Bar), vec![0, 6]);
+    }
+
+    #[test]
+    fn test_sync_beat() {
+        let mut grid = Grid::new(Quantize::Bar, 2);
+        grid.set_beat(10);
+
+        // a beat came a little later than the grid expected
+        (0..8).for_each(|_| grid.advance());
+        grid.sync_beat();
+        assert_eq!(grid.position, 10);
+
+        // the next bar came a little earlier
+        (0..12).for_each(|_| grid.advance());
+        grid.sync_beat();
+        assert_eq!(grid.position, 0);
+        assert!(grid.is_on_boundary());
+    }
+}
@@ -0,0 +1,176 @@
+/// Snapshots of a loop, taken before each overdub pass, so that passes can be undone and redone.
+///
+/// The snapshots are kept in a ring of slots the length of the loop, in storage which is allocated
+/// up front, so that recording one on the audio thread only copies. The oldest snapshots are
+/// overwritten once the storage is full.
+pub struct History {
+    samples: Vec<f32>,
+    /// The length of each snapshot, or 0 when there are none.
+    length: usize,
+    /// The slot holding the oldest snapshot.
+    first: usize,
+    /// The number of snapshots which can be undone, oldest first from `first`. The ones which can
+    /// be redone come straight after them, most recently undone first.
+    undo: usize,
+    redo: usize,
+}
+
+impl History {
+    /// `budget` is the most samples which can be kept, across both stacks.
+    pub fn new(budget: usize) -> Self {
+        Self {
+            samples: vec![0.0; budget],
+            length: 0,
+            first: 0,
+            undo: 0,
+            redo: 0,
+        }
+    }
+
+    /// Keeps a snapshot of the loop before it changes. Anything which was undone can no longer be
+    /// redone.
+    pub fn record(&mut self, snapshot: &[f32]) {
+        self.redo = 0;
+
+        if snapshot.len() != self.length {
+            self.clear();
+            self.length = snapshot.len();
+        }
+
+        let capacity = self.capacity();
+        if capacity == 0 {
+            return;
+        }
+
+        if self.undo == capacity {
+            self.first = (self.first + 1) % capacity;
+            self.undo -= 1;
+        }
+
+        self.slot_mut(self.undo).copy_from_slice(snapshot);
+        self.undo += 1;
+    }
+
+    /// Swaps `current` with the most recent snapshot. Returns false if there was nothing to undo.
+    pub fn undo(&mut self, current: &mut [f32]) -> bool {
+        if self.undo == 0 || current.len() != self.length {
+            return false;
+        }
+
+        self.undo -= 1;
+        self.redo += 1;
+        current.swap_with_slice(self.slot_mut(self.undo));
+        true
+    }
+
+    /// Swaps `current` with the most recently undone snapshot. Returns false if there was nothing
+    /// to redo.
+    pub fn redo(&mut self, current: &mut [f32]) -> bool {
+        if self.redo == 0 || current.len() != self.length {
+            return false;
+        }
+
+        current.swap_with_slice(self.slot_mut(self.undo));
+        self.undo += 1;
+        self.redo -= 1;
+        true
+    }
+
+    /// The most samples which can be kept.
+    pub fn budget(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo
+    }
+
+    pub fn redo_len(&self) -> usize {
+        self.redo
+    }
+
+    /// The snapshots which can be undone, oldest first.
+    pub fn snapshots(&self) -> impl Iterator<Item = &[f32]> {
+        (0..self.undo).map(move |index| &self.samples[self.slot_range(index)])
+    }
+
+    pub fn clear(&mut self) {
+        self.first = 0;
+        self.undo = 0;
+        self.redo = 0;
+    }
+
+    fn capacity(&self) -> usize {
+        self.samples.len().checked_div(self.length).unwrap_or(0)
+    }
+
+    fn slot_range(&self, index: usize) -> std::ops::Range<usize> {
+        let start = (self.first + index) % self.capacity() * self.length;
+        start..start + self.length
+    }
+
+    fn slot_mut(&mut self, index: usize) -> &mut [f32] {
+        let range = self.slot_range(index);
+        &mut self.samples[range]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_and_redo() {
+        let mut history = History::new(100);
+        let mut current = vec![1.0, 1.0];
+
+        history.record(&current);
+        current = vec![2.0, 2.0];
+
+        assert!(history.undo(&mut current));
+        assert_eq!(current, vec![1.0, 1.0]);
+        assert!(!history.undo(&mut current));
+
+        assert!(history.redo(&mut current));
+        assert_eq!(current, vec![2.0, 2.0]);
+        assert!(!history.redo(&mut current));
+    }
+
+    #[test]
+    fn test_budget_forgets_oldest() {
+        let mut history = History::new(4);
+        let mut current = vec![0.0, 0.0];
+
+        for value in [1.0, 2.0, 3.0] {
+            history.record(&current);
+            current = vec![value; 2];
+        }
+
+        assert!(history.undo(&mut current));
+        assert!(history.undo(&mut current));
+        assert_eq!(current, vec![1.0, 1.0]);
+        assert!(!history.undo(&mut current));
+    }
+
+    #[test]
+    fn test_recording_after_undo_forgets_redo() {
+        let mut history = History::new(100);
+        let mut current = vec![1.0];
+
+        history.record(&current);
+        current = vec![2.0];
+        history.record(&current);
+        current = vec![3.0];
+
+        assert!(history.undo(&mut current));
+        history.record(&current);
+        current = vec![4.0];
+
+        assert!(!history.redo(&mut current));
+        assert!(history.undo(&mut current));
+        assert_eq!(current, vec![2.0]);
+        assert!(history.undo(&mut current));
+        assert_eq!(current, vec![1.0]);
+        assert!(!history.undo(&mut current));
+    }
+}
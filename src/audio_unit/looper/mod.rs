@@ -0,0 +1,832 @@
+mod grid;
+mod history;
+mod save;
+mod status;
+mod track;
+
+pub use grid::Quantize;
+pub use status::{Event, SharedStatus, Status, TrackStatus};
+pub use track::{Speed, State};
+
+use crate::{audio_unit::AudioUnit, util, Result};
+use cpal::StreamConfig;
+use grid::Grid;
+use save::Saver;
+use std::{
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, Sender},
+};
+use track::Track;
+
+pub type TrackIndex = usize;
+
+#[derive(Debug)]
+pub enum Message {
+    /// Cycles a track from off, to recording, to playing, and back to off.
+    Toggle(TrackIndex),
+    /// Overdubs onto a playing track for one pass, starting at the top of its loop.
+    QueueOverdub(TrackIndex),
+    ToggleMute(TrackIndex),
+    /// Silences a track, while keeping its loop.
+    Stop(TrackIndex),
+    /// Plays a stopped track. It comes back in time with the other tracks, or from the top if
+    /// nothing else is playing.
+    Play(TrackIndex),
+    /// Plays a track through once, from the top.
+    OneShot(TrackIndex),
+    Clear(TrackIndex),
+    /// Doubles the length of a track's loop by repeating it.
+    Multiply(TrackIndex),
+    /// Switches a track between half and normal speed.
+    ToggleHalfSpeed(TrackIndex),
+    /// Switches a track between double and normal speed.
+    ToggleDoubleSpeed(TrackIndex),
+    /// Sets how much of the existing loop is kept on each overdub pass, for every track.
+    SetFeedback(f32),
+    /// Restores a track to how it was before its last overdub pass.
+    Undo(TrackIndex),
+    /// Puts back the last overdub pass which was undone on a track.
+    Redo(TrackIndex),
+    /// Plays the loops backwards, or forwards again. Overdubs line up with what is heard, so they
+    /// play backwards once the loops are turned around.
+    ToggleReverse,
+    /// Sets the length of a beat in frames, for quantizing, keeping the same place in the bar.
+    SetBeat(usize),
+    /// Marks the start of a beat of the tempo, which the grid moves to so that it doesn't drift.
+    SyncBeat,
+    /// Writes each track, and the layers which can be undone, to WAV files in the directory set
+    /// with `Looper::save_to`.
+    Save,
+}
+
+#[derive(Clone, Debug)]
+pub struct Parameters {
+    pub max_buffer_ms: u32,
+    /// How much audio is kept for undoing overdubs, shared evenly between the tracks.
+    pub max_history_ms: u32,
+    /// The level of each track.
+    pub levels: Vec<f32>,
+    pub quantize: Quantize,
+    pub beats_per_bar: usize,
+    /// When set, the first loop sets the tempo, as this many beats.
+    pub loop_beats: Option<usize>,
+    /// How long the end of each recording or overdub pass is crossfaded into its start.
+    pub crossfade_ms: f32,
+    /// How long tracks take to fade in and out as they start, stop, and are muted.
+    pub fade_ms: f32,
+    /// How much of the existing loop is kept on each overdub pass.
+    pub feedback: f32,
+}
+
+/// A set of synchronized loop tracks. The first loop recorded sets the master length, and every
+/// other loop is a multiple of it.
+pub struct Looper {
+    messages: Receiver<Message>,
+    events: Sender<Event>,
+    stream_config: StreamConfig,
+    channels: usize,
+    tracks: Vec<Track>,
+    master_length: Option<usize>,
+    reverse: bool,
+    grid: Grid,
+    loop_beats: Option<usize>,
+    /// Toggles waiting for the next beat or bar, and the state of the track when they arrived.
+    pending: Vec<(TrackIndex, State)>,
+    status: SharedStatus,
+    saver: Option<Saver>,
+}
+
+impl Looper {
+    pub fn new(
+        stream_config: &StreamConfig,
+        parameters: &Parameters,
+    ) -> (Self, Sender<Message>, Receiver<Event>) {
+        let (sender, receiver) = mpsc::channel();
+        let (event_sender, event_receiver) = mpsc::channel();
+
+        let channels = stream_config.channels as usize;
+        let max_frames = util::ms_in_samples(stream_config, parameters.max_buffer_ms) / channels;
+        let history_budget = util::ms_in_samples(stream_config, parameters.max_history_ms)
+            / parameters.levels.len().max(1);
+        let ms_in_frames = |ms: f32| (ms * stream_config.sample_rate.0 as f32 / 1_000.0) as usize;
+        let crossfade = ms_in_frames(parameters.crossfade_ms);
+        let fade = ms_in_frames(parameters.fade_ms);
+        let tracks = parameters
+            .levels
+            .iter()
+            .map(|level| {
+                let mut track = Track::new(
+                    channels,
+                    max_frames,
+                    *level,
+                    history_budget,
+                    crossfade,
+                    fade,
+                );
+                track.set_feedback(parameters.feedback);
+                track
+            })
+            .collect();
+
+        (
+            Self {
+                messages: receiver,
+                events: event_sender,
+                stream_config: stream_config.clone(),
+                channels,
+                tracks,
+                master_length: None,
+                reverse: false,
+                grid: Grid::new(parameters.quantize, parameters.beats_per_bar),
+                loop_beats: parameters.loop_beats,
+                pending: vec![],
+                status: SharedStatus::default(),
+                saver: None,
+            },
+            sender,
+            event_receiver,
+        )
+    }
+
+    /// Fills a track with a loop which was recorded ahead of time. It follows the same rules as a
+    /// recorded loop, so the first loaded or recorded loop sets the master length.
+    pub fn load(&mut self, index: TrackIndex, samples: &[f32]) {
+        if let Some(track) = self.tracks.get_mut(index) {
+            let length = track.load(samples, self.master_length);
+            self.set_master_length(length);
+        }
+    }
+
+    /// The looper's status, which is updated after each buffer.
+    pub fn status(&self) -> SharedStatus {
+        self.status.clone()
+    }
+
+    fn emit(&self, event: Event) {
+        // nothing may be listening, which is fine
+        let _ = self.events.send(event);
+    }
+
+    fn publish_status(&self) {
+        self.status.try_update(|status| {
+            status.tracks.clear();
+            status
+                .tracks
+                .extend(self.tracks.iter().enumerate().map(|(index, track)| {
+                    track.status(self.pending.iter().any(|(pending, _)| *pending == index))
+                }));
+            status.reverse = self.reverse;
+            status.master_length = self.master_length;
+        });
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        for message in messages {
+            self.process_message(message);
+        }
+    }
+
+    fn process_message(&mut self, message: Message) {
+        match message {
+            Message::Toggle(index) => self.toggle(index),
+            Message::SetBeat(frames_per_beat) => self.grid.set_beat(frames_per_beat),
+            Message::SyncBeat => self.grid.sync_beat(),
+            Message::QueueOverdub(index) => {
+                if let Some(track) = self.tracks.get_mut(index) {
+                    track.queue_overdub();
+
+                    if track.state() == State::PlayingAwaitingOverdub {
+                        self.emit(Event::State(index, State::PlayingAwaitingOverdub));
+                    }
+                }
+            }
+            Message::ToggleMute(index) => {
+                if let Some(track) = self.tracks.get_mut(index) {
+                    track.toggle_mute();
+                    let is_muted = track.is_muted();
+                    self.emit(Event::Muted(index, is_muted));
+                }
+            }
+            Message::Stop(index) => {
+                if let Some(track) = self.tracks.get_mut(index) {
+                    if track.stop() {
+                        self.emit(Event::State(index, State::Stopped));
+                    }
+                }
+            }
+            Message::Play(index) => self.play(index),
+            Message::OneShot(index) => self.one_shot(index),
+            Message::Clear(index) => {
+                if index < self.tracks.len() {
+                    self.pending.retain(|(pending, _)| *pending != index);
+                    self.clear(index);
+                    self.emit(Event::Cleared(index));
+                }
+            }
+            Message::Multiply(index) => {
+                if let Some(track) = self.tracks.get_mut(index) {
+                    let is_multiplied = track.multiply();
+                    self.emit(Event::Multiplied(index, is_multiplied));
+                }
+            }
+            Message::ToggleHalfSpeed(index) => self.toggle_speed(index, Speed::Half),
+            Message::ToggleDoubleSpeed(index) => self.toggle_speed(index, Speed::Double),
+            Message::SetFeedback(feedback) => {
+                for track in &mut self.tracks {
+                    track.set_feedback(feedback);
+                }
+            }
+            Message::Undo(index) => {
+                if let Some(track) = self.tracks.get_mut(index) {
+                    let is_undone = track.undo();
+                    self.emit(Event::Undone(index, is_undone));
+                }
+            }
+            Message::Redo(index) => {
+                if let Some(track) = self.tracks.get_mut(index) {
+                    let is_redone = track.redo();
+                    self.emit(Event::Redone(index, is_redone));
+                }
+            }
+            Message::Save => self.save(),
+            Message::ToggleReverse => {
+                self.reverse = !self.reverse;
+                self.emit(Event::Reversed(self.reverse));
+            }
+        }
+    }
+
+    fn toggle(&mut self, index: TrackIndex) {
+        let state = match self.tracks.get(index) {
+            Some(track) => track.state(),
+            None => return,
+        };
+
+        // only starting and stopping recording wait, so that loops are a whole number of beats
+        if self.grid.is_quantized() && matches!(state, State::Off | State::Recording) {
+            match self
+                .pending
+                .iter()
+                .position(|(pending, _)| *pending == index)
+            {
+                Some(position) => {
+                    self.pending.remove(position);
+                    self.emit(Event::Cancelled(index));
+                }
+                None => {
+                    self.pending.push((index, state));
+                    self.emit(Event::Waiting(index, self.grid.quantize()));
+                }
+            }
+            return;
+        }
+
+        self.toggle_now(index, state);
+    }
+
+    /// Carries out the toggles which were waiting for this beat or bar. Tracks which changed in the
+    /// meantime (eg. by running out of space) are left alone.
+    fn toggle_pending(&mut self) {
+        let pending: Vec<_> = self.pending.drain(..).collect();
+
+        for (index, state) in pending {
+            if self.tracks[index].state() == state {
+                self.toggle_now(index, state);
+            }
+        }
+    }
+
+    fn toggle_now(&mut self, index: TrackIndex, state: State) {
+        match state {
+            State::Off => self.tracks[index].start_recording(),
+            State::Recording => self.finish_recording(index),
+            _ => self.fade_out_and_clear(index),
+        }
+
+        self.emit(Event::State(index, self.tracks[index].state()));
+    }
+
+    /// Starts the thread which writes saved tracks to WAV files in `directory`.
+    pub fn save_to(&mut self, directory: PathBuf) {
+        let capacity = self.tracks.iter().map(Track::max_saved_samples).sum();
+
+        self.saver = Some(Saver::new(
+            directory,
+            &self.stream_config,
+            self.tracks.len(),
+            capacity,
+            self.events.clone(),
+        ));
+    }
+
+    fn save(&self) {
+        let saver = match &self.saver {
+            Some(saver) => saver,
+            None => return,
+        };
+
+        let loops = self
+            .tracks
+            .iter()
+            .enumerate()
+            .filter(|(_, track)| track.length() > 0)
+            .flat_map(|(index, track)| {
+                track
+                    .layers()
+                    .enumerate()
+                    .map(move |(layer, samples)| (index, Some(layer), samples))
+                    .chain(std::iter::once((index, None, track.samples())))
+            });
+
+        if !saver.save(loops) {
+            self.emit(Event::StillSaving);
+        }
+    }
+
+    fn play(&mut self, index: TrackIndex) {
+        if self.tracks.get(index).is_some_and(Track::has_loop) {
+            self.rewind_if_alone(index);
+
+            if self.tracks[index].play() {
+                self.emit(Event::State(index, State::Playing));
+            }
+        }
+    }
+
+    fn one_shot(&mut self, index: TrackIndex) {
+        if self.tracks.get(index).is_some_and(Track::has_loop) {
+            self.rewind_if_alone(index);
+
+            if self.tracks[index].one_shot() {
+                let state = self.tracks[index].state();
+                self.emit(Event::State(index, state));
+            }
+        }
+    }
+
+    /// When nothing else is playing, moves every loop back together, so that the track starts from
+    /// the top and the others stay in time with it.
+    fn rewind_if_alone(&mut self, index: TrackIndex) {
+        let is_alone = self
+            .tracks
+            .iter()
+            .enumerate()
+            .all(|(other, track)| other == index || !track.is_playing());
+
+        if !is_alone || self.tracks[index].is_playing() {
+            return;
+        }
+
+        let frames = self.tracks[index].frames_since_top();
+        for track in &mut self.tracks {
+            track.rewind(frames);
+        }
+        self.grid.rewind(frames);
+    }
+
+    fn toggle_speed(&mut self, index: TrackIndex, speed: Speed) {
+        if let Some(track) = self.tracks.get_mut(index) {
+            track.toggle_speed(speed);
+            let speed = track.speed();
+            self.emit(Event::Speed(index, speed));
+        }
+    }
+
+    fn finish_recording(&mut self, index: TrackIndex) {
+        let length = self.tracks[index].finish_recording(self.master_length);
+        self.set_master_length(length);
+    }
+
+    /// Sets the master length if this is the first loop, along with the tempo if it follows the
+    /// loop.
+    fn set_master_length(&mut self, length: usize) {
+        if self.master_length.is_some() {
+            return;
+        }
+
+        self.master_length = Some(length);
+
+        if let Some(loop_beats) = self.loop_beats {
+            let frames_per_beat = length / loop_beats.max(1);
+            // the first loop carries on from its start, so the bar does too
+            self.grid.start_bar(frames_per_beat);
+            self.emit(Event::Tempo(frames_per_beat));
+        }
+    }
+
+    fn clear(&mut self, index: TrackIndex) {
+        self.tracks[index].clear();
+        self.forget_empty_master_length();
+    }
+
+    fn fade_out_and_clear(&mut self, index: TrackIndex) {
+        if self.tracks[index].fade_out_and_clear() {
+            self.forget_empty_master_length();
+        }
+    }
+
+    /// Once every track is empty, the next loop sets a new master length.
+    fn forget_empty_master_length(&mut self) {
+        if self.tracks.iter().all(|track| track.length() == 0) {
+            self.master_length = None;
+        }
+    }
+
+    fn process_samples(&mut self, input: &[f32], output: &mut [f32]) {
+        util::zero_slice(output);
+
+        for (input, output) in input
+            .chunks(self.channels)
+            .zip(output.chunks_mut(self.channels))
+        {
+            if !self.pending.is_empty() && self.grid.is_on_boundary() {
+                self.toggle_pending();
+            }
+
+            for index in 0..self.tracks.len() {
+                match self.tracks[index].process_frame(input, output, self.reverse) {
+                    Some(State::Playing) if self.tracks[index].state() == State::Recording => {
+                        self.emit(Event::OutOfSpace(index));
+                        self.finish_recording(index);
+                        self.emit(Event::State(index, State::Playing));
+                    }
+                    Some(State::Off) => {
+                        // a track which was faded out has been cleared
+                        self.forget_empty_master_length();
+                        self.emit(Event::State(index, State::Off));
+                    }
+                    Some(state) => self.emit(Event::State(index, state)),
+                    None => (),
+                }
+            }
+
+            self.grid.advance();
+        }
+    }
+}
+
+impl AudioUnit for Looper {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_messages();
+        self.process_samples(input, output);
+        self.publish_status();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::wav;
+    use cpal::{BufferSize, SampleRate};
+    use std::f32::consts::FRAC_PI_4;
+
+    fn parameters(tracks: usize) -> Parameters {
+        Parameters {
+            max_buffer_ms: 100,
+            max_history_ms: 100,
+            levels: vec![1.0; tracks],
+            quantize: Quantize::Off,
+            beats_per_bar: 4,
+            loop_beats: None,
+            crossfade_ms: 0.0,
+            fade_ms: 0.0,
+            feedback: 1.0,
+        }
+    }
+
+    fn looper_with(parameters: &Parameters) -> (Looper, Sender<Message>, Receiver<Event>) {
+        let stream_config = StreamConfig {
+            channels: 1,
+            sample_rate: SampleRate(1_000),
+            buffer_size: BufferSize::Default,
+        };
+        Looper::new(&stream_config, parameters)
+    }
+
+    fn looper(tracks: usize) -> (Looper, Sender<Message>) {
+        let (looper, messages, _) = looper_with(&parameters(tracks));
+        (looper, messages)
+    }
+
+    fn process(looper: &mut Looper, input: &[f32]) -> Vec<f32> {
+        let mut output = vec![0.0; input.len()];
+        looper.process(input, &mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn test_record_and_play() {
+        let (mut looper, messages) = looper(1);
+
+        messages.send(Message::Toggle(0)).unwrap();
+        process(&mut looper, &[1.0, 2.0, 3.0]);
+        messages.send(Message::Toggle(0)).unwrap();
+
+        assert_eq!(
+            process(&mut looper, &[0.0; 6]),
+            vec![1.0, 2.0, 3.0, 1.0, 2.0, 3.0]
+        );
+    }
+
+    #[test]
+    fn test_tracks_are_multiples_of_the_first() {
+        let (mut looper, messages) = looper(2);
+
+        messages.send(Message::Toggle(0)).unwrap();
+        process(&mut looper, &[1.0, 0.0, 0.0]);
+        messages.send(Message::Toggle(0)).unwrap();
+        messages.send(Message::Toggle(1)).unwrap();
+        process(&mut looper, &[0.0, 0.0, 0.0, 10.0]);
+        messages.send(Message::Toggle(1)).unwrap();
+
+        // the second track carries on from where it stopped recording, so that it stays in time
+        assert_eq!(
+            process(&mut looper, &[0.0; 6]),
+            vec![0.0, 0.0, 1.0, 0.0, 0.0, 11.0]
+        );
+        assert_eq!(looper.tracks[1].length(), 6);
+    }
+
+    #[test]
+    fn test_quantized_to_beat() {
+        let parameters = Parameters {
+            quantize: Quantize::Beat,
+            ..parameters(1)
+        };
+        let (mut looper, messages, _) = looper_with(&parameters);
+
+        messages.send(Message::SetBeat(2)).unwrap();
+        process(&mut looper, &[0.0]);
+        // recording waits for the start of the next beat, and stops at the end of a beat
+        messages.send(Message::Toggle(0)).unwrap();
+        process(&mut looper, &[9.0, 1.0, 2.0, 3.0]);
+        messages.send(Message::Toggle(0)).unwrap();
+        process(&mut looper, &[4.0, 0.0]);
+
+        assert_eq!(looper.tracks[0].length(), 4);
+        assert_eq!(process(&mut looper, &[0.0; 4]), vec![2.0, 3.0, 4.0, 1.0]);
+    }
+
+    #[test]
+    fn test_tempo_from_first_loop() {
+        let parameters = Parameters {
+            loop_beats: Some(4),
+            ..parameters(1)
+        };
+        let (mut looper, messages, events) = looper_with(&parameters);
+
+        messages.send(Message::Toggle(0)).unwrap();
+        process(&mut looper, &[0.0; 8]);
+        messages.send(Message::Toggle(0)).unwrap();
+        process(&mut looper, &[0.0]);
+
+        assert!(events
+            .try_iter()
+            .any(|event| matches!(event, Event::Tempo(2))));
+    }
+
+    #[test]
+    fn test_save() {
+        let directory = std::env::temp_dir().join("pedals-test-looper-save");
+        let (mut looper, messages, events) = looper_with(&parameters(1));
+        looper.save_to(directory.clone());
+
+        messages.send(Message::Toggle(0)).unwrap();
+        process(&mut looper, &[1.0, 2.0, 3.0]);
+        messages.send(Message::Toggle(0)).unwrap();
+        messages.send(Message::Save).unwrap();
+        process(&mut looper, &[0.0]);
+
+        let path = directory.join("track-1.wav");
+        let saved = events
+            .iter()
+            .find_map(|event| match event {
+                Event::Saved(path) => Some(path),
+                Event::SaveFailed(path, error) => panic!("{}: {}", path.display(), error),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(saved, path);
+
+        let stream_config = StreamConfig {
+            channels: 1,
+            sample_rate: SampleRate(1_000),
+            buffer_size: BufferSize::Default,
+        };
+        assert_eq!(
+            wav::read(&path, &stream_config).unwrap(),
+            vec![1.0, 2.0, 3.0]
+        );
+    }
+
+    #[test]
+    fn test_status() {
+        let (mut looper, messages, events) = looper_with(&parameters(2));
+        let status = looper.status();
+
+        messages.send(Message::Toggle(0)).unwrap();
+        process(&mut looper, &[1.0, 2.0, 3.0]);
+        messages.send(Message::Toggle(0)).unwrap();
+        process(&mut looper, &[0.0; 4]);
+
+        let status = status.get().unwrap();
+        assert_eq!(status.master_length, Some(3));
+        assert_eq!(status.tracks.len(), 2);
+        assert_eq!(status.tracks[0].state, State::Playing);
+        assert_eq!(status.tracks[0].length, 3);
+        assert_eq!(status.tracks[0].position, 1);
+        assert_eq!(status.tracks[1].state, State::Off);
+
+        let states = events
+            .try_iter()
+            .filter_map(|event| match event {
+                Event::State(0, state) => Some(state),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(states, vec![State::Recording, State::Playing]);
+    }
+
+    fn recorded(input: &[f32]) -> (Looper, Sender<Message>) {
+        let (mut looper, messages) = looper(1);
+
+        messages.send(Message::Toggle(0)).unwrap();
+        process(&mut looper, input);
+        messages.send(Message::Toggle(0)).unwrap();
+
+        (looper, messages)
+    }
+
+    #[test]
+    fn test_stop_and_play_from_the_top() {
+        let (mut looper, messages) = recorded(&[1.0, 2.0, 3.0]);
+
+        process(&mut looper, &[0.0]);
+        messages.send(Message::Stop(0)).unwrap();
+        assert_eq!(process(&mut looper, &[0.0]), vec![0.0]);
+
+        messages.send(Message::Play(0)).unwrap();
+        assert_eq!(process(&mut looper, &[0.0; 3]), vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_one_shot() {
+        let (mut looper, messages) = recorded(&[1.0, 2.0]);
+
+        process(&mut looper, &[0.0]);
+        // waits for the top of the loop, plays it once, then stops
+        messages.send(Message::OneShot(0)).unwrap();
+        assert_eq!(
+            process(&mut looper, &[0.0; 5]),
+            vec![0.0, 1.0, 2.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn test_multiply() {
+        let (mut looper, messages) = recorded(&[1.0, 2.0]);
+
+        messages.send(Message::Multiply(0)).unwrap();
+        assert_eq!(process(&mut looper, &[0.0; 4]), vec![1.0, 2.0, 1.0, 2.0]);
+        assert_eq!(looper.tracks[0].length(), 4);
+    }
+
+    #[test]
+    fn test_half_speed() {
+        let (mut looper, messages) = recorded(&[0.0, 2.0]);
+
+        messages.send(Message::ToggleHalfSpeed(0)).unwrap();
+        assert_eq!(process(&mut looper, &[0.0; 4]), vec![0.0, 1.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn test_double_speed_one_frame_loop() {
+        let (mut looper, messages) = recorded(&[1.0]);
+
+        // each step is longer than the loop
+        messages.send(Message::ToggleDoubleSpeed(0)).unwrap();
+        assert_eq!(process(&mut looper, &[0.0; 300]), vec![1.0; 300]);
+    }
+
+    #[test]
+    fn test_crossfade() {
+        let parameters = Parameters {
+            crossfade_ms: 2.0,
+            ..parameters(1)
+        };
+        let (mut looper, messages, _) = looper_with(&parameters);
+
+        messages.send(Message::Toggle(0)).unwrap();
+        process(&mut looper, &[1.0; 4]);
+        messages.send(Message::Toggle(0)).unwrap();
+        // the input after recording stops is faded out over the start of the loop, as it fades in
+        process(&mut looper, &[2.0, 2.0, 0.0, 0.0]);
+
+        let output = process(&mut looper, &[0.0; 4]);
+        let half = FRAC_PI_4.sin();
+        assert_eq!(output, vec![2.0, half + 2.0 * half, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_fade_out_on_stop() {
+        let parameters = Parameters {
+            fade_ms: 2.0,
+            ..parameters(1)
+        };
+        let (mut looper, messages, _) = looper_with(&parameters);
+
+        messages.send(Message::Toggle(0)).unwrap();
+        process(&mut looper, &[1.0; 4]);
+        messages.send(Message::Toggle(0)).unwrap();
+
+        assert_eq!(process(&mut looper, &[0.0; 2]), vec![0.5, 1.0]);
+        messages.send(Message::Stop(0)).unwrap();
+        assert_eq!(process(&mut looper, &[0.0; 3]), vec![0.5, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_fade_out_on_toggle() {
+        let parameters = Parameters {
+            fade_ms: 2.0,
+            ..parameters(1)
+        };
+        let (mut looper, messages, events) = looper_with(&parameters);
+
+        messages.send(Message::Toggle(0)).unwrap();
+        process(&mut looper, &[1.0; 4]);
+        messages.send(Message::Toggle(0)).unwrap();
+
+        assert_eq!(process(&mut looper, &[0.0; 2]), vec![0.5, 1.0]);
+        // the loop is only thrown away once it has faded out
+        messages.send(Message::Toggle(0)).unwrap();
+        assert_eq!(process(&mut looper, &[0.0; 3]), vec![0.5, 0.0, 0.0]);
+        assert_eq!(looper.tracks[0].state(), State::Off);
+        assert_eq!(looper.master_length, None);
+
+        let states = events
+            .try_iter()
+            .filter_map(|event| match event {
+                Event::State(0, state) => Some(state),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            states,
+            vec![
+                State::Recording,
+                State::Playing,
+                State::Clearing,
+                State::Off
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overdub_feedback() {
+        let (mut looper, messages) = recorded(&[1.0, 2.0]);
+
+        messages.send(Message::SetFeedback(0.5)).unwrap();
+        messages.send(Message::QueueOverdub(0)).unwrap();
+        // the loop is played at full level, while fading by half under the overdub
+        assert_eq!(
+            process(&mut looper, &[0.0, 0.0, 1.0, 1.0]),
+            vec![1.0, 2.0, 1.0, 2.0]
+        );
+
+        assert_eq!(process(&mut looper, &[0.0; 2]), vec![1.5, 2.0]);
+    }
+
+    #[test]
+    fn test_load() {
+        let (mut looper, _) = looper(2);
+
+        looper.load(0, &[1.0, 2.0]);
+        looper.load(1, &[10.0, 10.0, 10.0]);
+
+        assert_eq!(looper.tracks[1].length(), 4);
+        assert_eq!(process(&mut looper, &[0.0; 4]), vec![11.0, 12.0, 11.0, 2.0]);
+    }
+
+    #[test]
+    fn test_undo_and_redo_overdub() {
+        let (mut looper, messages) = looper(1);
+
+        messages.send(Message::Toggle(0)).unwrap();
+        process(&mut looper, &[1.0, 2.0]);
+        messages.send(Message::Toggle(0)).unwrap();
+        messages.send(Message::QueueOverdub(0)).unwrap();
+        // finish the pass that was playing when overdub was queued, then overdub for one pass
+        process(&mut looper, &[0.0, 0.0, 10.0, 10.0]);
+
+        assert_eq!(process(&mut looper, &[0.0; 2]), vec![11.0, 12.0]);
+
+        messages.send(Message::Undo(0)).unwrap();
+        assert_eq!(process(&mut looper, &[0.0; 2]), vec![1.0, 2.0]);
+
+        messages.send(Message::Redo(0)).unwrap();
+        assert_eq!(process(&mut looper, &[0.0; 2]), vec![11.0, 12.0]);
+    }
+}
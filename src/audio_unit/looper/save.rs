@@ -0,0 +1,127 @@
+use super::{Event, TrackIndex};
+use crate::util::wav;
+use cpal::StreamConfig;
+use std::{
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+/// A loop to be written to a file: the track, the undo layer if it isn't the loop as it is now, and
+/// where its samples are.
+type File = (TrackIndex, Option<usize>, Range<usize>);
+
+#[derive(Default)]
+struct Copy {
+    samples: Vec<f32>,
+    files: Vec<File>,
+}
+
+/// Saves tracks to WAV files. The audio thread copies them into storage which was allocated up
+/// front, and a long-lived thread writes the files, so that saving never allocates or waits on the
+/// disk while processing audio.
+pub struct Saver {
+    copy: Arc<Mutex<Copy>>,
+    requests: Sender<()>,
+}
+
+impl Saver {
+    /// The most undo layers which are saved for each track.
+    const MAX_LAYERS: usize = 32;
+
+    /// `capacity` is the most samples which will be copied at once, across every track and layer.
+    pub fn new(
+        directory: PathBuf,
+        stream_config: &StreamConfig,
+        tracks: usize,
+        capacity: usize,
+        events: Sender<Event>,
+    ) -> Self {
+        let copy = Arc::new(Mutex::new(Copy {
+            samples: Vec::with_capacity(capacity),
+            files: Vec::with_capacity(tracks * (Self::MAX_LAYERS + 1)),
+        }));
+        let (requests, receiver) = mpsc::channel();
+
+        let writer_copy = copy.clone();
+        let stream_config = stream_config.clone();
+
+        thread::spawn(move || {
+            for () in receiver {
+                // the audio thread won't copy over the files while they're being written
+                if let Ok(mut copy) = writer_copy.lock() {
+                    for event in Self::write(&directory, &stream_config, &copy) {
+                        // nothing may be listening, which is fine
+                        let _ = events.send(event);
+                    }
+
+                    copy.samples.clear();
+                    copy.files.clear();
+                }
+            }
+        });
+
+        Self { copy, requests }
+    }
+
+    /// Copies the loops, and asks for them to be written. Returns false if the last save is still
+    /// being written.
+    pub fn save<'a>(
+        &self,
+        loops: impl Iterator<Item = (TrackIndex, Option<usize>, &'a [f32])>,
+    ) -> bool {
+        let mut copy = match self.copy.try_lock() {
+            Ok(copy) if copy.files.is_empty() => copy,
+            _ => return false,
+        };
+        let Copy { samples, files } = &mut *copy;
+
+        for (track, layer, loop_samples) in loops {
+            let is_full = layer.is_some_and(|layer| layer >= Self::MAX_LAYERS)
+                || files.len() == files.capacity()
+                || samples.len() + loop_samples.len() > samples.capacity();
+            if is_full {
+                continue;
+            }
+
+            let start = samples.len();
+            samples.extend_from_slice(loop_samples);
+            files.push((track, layer, start..samples.len()));
+        }
+
+        // the writer only stops if the looper has been dropped
+        let _ = self.requests.send(());
+
+        true
+    }
+
+    fn write(directory: &Path, stream_config: &StreamConfig, copy: &Copy) -> Vec<Event> {
+        if let Err(error) = fs::create_dir_all(directory) {
+            return vec![Event::SaveFailed(
+                directory.to_path_buf(),
+                error.to_string(),
+            )];
+        }
+
+        copy.files
+            .iter()
+            .map(|(track, layer, range)| {
+                let name = match layer {
+                    Some(layer) => format!("track-{}-layer-{}.wav", track + 1, layer + 1),
+                    None => format!("track-{}.wav", track + 1),
+                };
+                let path = directory.join(name);
+
+                match wav::write(&path, stream_config, &copy.samples[range.clone()]) {
+                    Ok(()) => Event::Saved(path),
+                    Err(error) => Event::SaveFailed(path, error.to_string()),
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,129 @@
+use super::{Quantize, Speed, State, TrackIndex};
+use crate::Result;
+use anyhow::anyhow;
+use std::{
+    fmt,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+/// Something which happened in the looper, for reporting to the player.
+#[derive(Debug)]
+pub enum Event {
+    /// A track changed state.
+    State(TrackIndex, State),
+    /// Starting or stopping recording is waiting for the next beat or bar.
+    Waiting(TrackIndex, Quantize),
+    /// A track which was waiting for the next beat or bar isn't anymore.
+    Cancelled(TrackIndex),
+    /// A track filled its buffer while recording, so it started playing.
+    OutOfSpace(TrackIndex),
+    Cleared(TrackIndex),
+    Muted(TrackIndex, bool),
+    Speed(TrackIndex, Speed),
+    /// A track's loop was doubled, or there wasn't space to.
+    Multiplied(TrackIndex, bool),
+    /// A track's last overdub pass was undone, or there was nothing to undo.
+    Undone(TrackIndex, bool),
+    /// A track's last undone overdub pass was put back, or there was nothing to redo.
+    Redone(TrackIndex, bool),
+    Reversed(bool),
+    /// The first loop set the tempo. The length of a beat, in frames.
+    Tempo(usize),
+    Saved(PathBuf),
+    /// Saving was asked for while the last save was still being written.
+    StillSaving,
+    /// A file couldn't be saved, and why.
+    SaveFailed(PathBuf, String),
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let on_off = |is_on: bool| if is_on { "on" } else { "off" };
+
+        match self {
+            Event::State(index, state) => write!(f, "track {} {:?}", index + 1, state),
+            Event::Waiting(index, quantize) => {
+                write!(f, "track {} waiting for the next {:?}", index + 1, quantize)
+            }
+            Event::Cancelled(index) => write!(f, "track {} cancelled", index + 1),
+            Event::OutOfSpace(index) => write!(
+                f,
+                "track {} out of space in the buffer. switching to playback",
+                index + 1
+            ),
+            Event::Cleared(index) => write!(f, "track {} cleared", index + 1),
+            Event::Muted(index, is_muted) => write!(
+                f,
+                "track {} {}",
+                index + 1,
+                if *is_muted { "muted" } else { "unmuted" }
+            ),
+            Event::Speed(index, speed) => write!(f, "track {} {:?} speed", index + 1, speed),
+            Event::Multiplied(index, true) => write!(f, "track {} multiplied", index + 1),
+            Event::Multiplied(index, false) => {
+                write!(f, "track {} can't be multiplied", index + 1)
+            }
+            Event::Undone(index, true) => write!(f, "track {} undo overdub", index + 1),
+            Event::Undone(index, false) => write!(f, "track {} nothing to undo", index + 1),
+            Event::Redone(index, true) => write!(f, "track {} redo overdub", index + 1),
+            Event::Redone(index, false) => write!(f, "track {} nothing to redo", index + 1),
+            Event::Reversed(is_reversed) => write!(f, "reverse {}", on_off(*is_reversed)),
+            Event::Tempo(frames_per_beat) => write!(f, "tempo {} frames per beat", frames_per_beat),
+            Event::Saved(path) => write!(f, "saved {}", path.display()),
+            Event::StillSaving => write!(f, "still saving the last loops"),
+            Event::SaveFailed(path, error) => {
+                write!(f, "failed to save {}: {}", path.display(), error)
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TrackStatus {
+    pub state: State,
+    pub speed: Speed,
+    pub is_muted: bool,
+    /// Whether starting or stopping recording is waiting for the next beat or bar.
+    pub is_waiting: bool,
+    /// The length of the loop, in frames.
+    pub length: usize,
+    /// The number of frames since the top of the loop.
+    pub position: usize,
+    /// The number of overdub passes which can be undone.
+    pub undo_layers: usize,
+    /// The number of undone overdub passes which can be redone.
+    pub redo_layers: usize,
+}
+
+/// A snapshot of the looper, as of the end of the last buffer it processed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Status {
+    pub tracks: Vec<TrackStatus>,
+    pub reverse: bool,
+    /// The length of the first loop, in frames. The others are multiples of it.
+    pub master_length: Option<usize>,
+}
+
+/// The looper's latest status, which can be read from other threads.
+#[derive(Clone, Debug, Default)]
+pub struct SharedStatus(Arc<Mutex<Status>>);
+
+impl SharedStatus {
+    pub fn get(&self) -> Result<Status> {
+        let status = self
+            .0
+            .lock()
+            .map_err(|_| anyhow!("looper status lock was poisoned"))?;
+
+        Ok(status.clone())
+    }
+
+    /// Updates the status, unless it is being read, so that the audio thread never waits. It will
+    /// be updated again after the next buffer.
+    pub(super) fn try_update(&self, update: impl FnOnce(&mut Status)) {
+        if let Ok(mut status) = self.0.try_lock() {
+            update(&mut status);
+        }
+    }
+}
@@ -0,0 +1,553 @@
+use super::{history::History, TrackStatus};
+use std::f32::consts::FRAC_PI_2;
+use State::*;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum State {
+    Off,
+    Recording,
+    Playing,
+    PlayingAwaitingOverdub,
+    Overdubbing,
+    /// Silent, but still keeping time with the other tracks, so that it comes back in where it
+    /// would have been.
+    Stopped,
+    /// Silent until the top of the loop, when it plays the loop through once.
+    AwaitingOneShot,
+    PlayingOnce,
+    /// Fading out, after which the loop is thrown away.
+    Clearing,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Speed {
+    Half,
+    Normal,
+    Double,
+}
+
+impl Speed {
+    /// How far the track moves each frame, in half frames.
+    fn step(self) -> usize {
+        match self {
+            Speed::Half => 1,
+            Speed::Normal => 2,
+            Speed::Double => 4,
+        }
+    }
+}
+
+/// A single loop. Every track's position advances together, one frame at a time, so tracks stay
+/// in sync with each other once they're recorded.
+pub struct Track {
+    channels: usize,
+    buffer: Vec<f32>,
+    state: State,
+    /// The length of the loop in frames, once it has been recorded.
+    length: usize,
+    /// The position in half frames, so that the loop can be played at half speed.
+    position: usize,
+    speed: Speed,
+    level: f32,
+    is_muted: bool,
+    history: History,
+    /// The length of the crossfade at the seam, in frames.
+    crossfade: usize,
+    /// The number of frames since the current recording or overdub pass started.
+    layer_frames: usize,
+    /// The number of frames of input which have been faded out past the end of the last recording
+    /// or overdub pass, while the tail is being captured.
+    tail_frames: Option<usize>,
+    /// Fades the output in and out as the track starts, stops, and is muted.
+    envelope: f32,
+    fade_step: f32,
+    /// How much of the existing loop is kept on each overdub pass.
+    feedback: f32,
+}
+
+impl Track {
+    const SUBFRAMES: usize = 2;
+
+    pub fn new(
+        channels: usize,
+        max_frames: usize,
+        level: f32,
+        history_budget: usize,
+        crossfade: usize,
+        fade: usize,
+    ) -> Self {
+        Self {
+            channels,
+            buffer: vec![0.0; max_frames * channels],
+            state: Off,
+            length: 0,
+            position: 0,
+            speed: Speed::Normal,
+            level,
+            is_muted: false,
+            history: History::new(history_budget),
+            crossfade,
+            layer_frames: 0,
+            tail_frames: None,
+            envelope: 0.0,
+            fade_step: 1.0 / fade.max(1) as f32,
+            feedback: 1.0,
+        }
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.is_muted
+    }
+
+    /// Whether the track has a loop, whether or not it is playing.
+    pub fn has_loop(&self) -> bool {
+        !matches!(self.state, Off | Recording)
+    }
+
+    /// Whether the loop can be heard.
+    pub fn is_playing(&self) -> bool {
+        matches!(
+            self.state,
+            Playing | PlayingAwaitingOverdub | Overdubbing | PlayingOnce
+        )
+    }
+
+    /// The recorded loop, as interleaved samples.
+    pub fn samples(&self) -> &[f32] {
+        &self.buffer[..self.length * self.channels]
+    }
+
+    /// The loop as it was before each overdub pass which can still be undone, oldest first.
+    pub fn layers(&self) -> impl Iterator<Item = &[f32]> {
+        self.history.snapshots()
+    }
+
+    /// The most samples which saving the loop and its layers can take.
+    pub fn max_saved_samples(&self) -> usize {
+        self.buffer.len() + self.history.budget()
+    }
+
+    fn max_frames(&self) -> usize {
+        self.buffer.len() / self.channels
+    }
+
+    fn loop_subframes(&self) -> usize {
+        self.length * Self::SUBFRAMES
+    }
+
+    /// The gain at the start of a recording or overdub pass. Equal power, so that the crossfade
+    /// doesn't dip in level.
+    fn fade_in(&self, frame: usize) -> f32 {
+        if frame >= self.crossfade {
+            1.0
+        } else {
+            (FRAC_PI_2 * frame as f32 / self.crossfade as f32).sin()
+        }
+    }
+
+    /// The gain of the tail after the end of a recording or overdub pass.
+    fn fade_out(&self, frame: usize) -> f32 {
+        if frame >= self.crossfade {
+            0.0
+        } else {
+            (FRAC_PI_2 * frame as f32 / self.crossfade as f32).cos()
+        }
+    }
+
+    /// How far the loop has faded by the feedback on this frame, from 0 to 1. It fades in over the
+    /// start of an overdub pass, and the rest is made up by the tail after the pass, so that the
+    /// seam doesn't jump in level.
+    fn decay_weight(&self) -> f32 {
+        let linear = |frame: usize| {
+            if self.crossfade == 0 {
+                1.0
+            } else {
+                (frame as f32 / self.crossfade as f32).min(1.0)
+            }
+        };
+
+        let layer = if self.state == Overdubbing {
+            linear(self.layer_frames)
+        } else {
+            0.0
+        };
+        let tail = self
+            .tail_frames
+            .map_or(0.0, |tail_frames| 1.0 - linear(tail_frames));
+
+        layer + tail
+    }
+
+    /// Keeps adding the input after a recording or overdub pass ends, fading out over the start of
+    /// the pass as it fades in, so that the seam is a crossfade rather than a cut.
+    fn start_tail(&mut self) {
+        self.tail_frames = (self.crossfade > 0).then_some(0);
+    }
+
+    pub fn start_recording(&mut self) {
+        self.state = Recording;
+        self.length = 0;
+        self.position = 0;
+        self.speed = Speed::Normal;
+    }
+
+    /// Stops recording and starts playing the loop. The first loop sets the master length, and
+    /// later loops are padded with silence up to a multiple of it, so that they stay in time.
+    /// Returns the length of the loop.
+    pub fn finish_recording(&mut self, master_length: Option<usize>) -> usize {
+        let recorded = (self.position / Self::SUBFRAMES).max(1);
+
+        self.length = match master_length {
+            Some(master_length) => {
+                let repeats = recorded.div_ceil(master_length);
+                let max_repeats = (self.max_frames() / master_length).max(1);
+                repeats.min(max_repeats) * master_length
+            }
+            None => recorded,
+        };
+
+        let recorded_samples = recorded.min(self.length) * self.channels;
+        let length_samples = self.length * self.channels;
+        self.buffer[recorded_samples..length_samples]
+            .iter_mut()
+            .for_each(|sample| *sample = 0.0);
+
+        // carry on from where recording stopped, so that the loop lines up with the others
+        self.position = (recorded % self.length) * Self::SUBFRAMES;
+        self.state = Playing;
+        self.start_tail();
+
+        self.length
+    }
+
+    /// Replaces the track with a loop which was recorded ahead of time, and starts playing it from
+    /// the top. Returns the length of the loop.
+    pub fn load(&mut self, samples: &[f32], master_length: Option<usize>) -> usize {
+        let frames = (samples.len() / self.channels).min(self.max_frames());
+        let length = frames * self.channels;
+        self.buffer[..length].copy_from_slice(&samples[..length]);
+
+        self.history.clear();
+        self.position = frames * Self::SUBFRAMES;
+        self.finish_recording(master_length);
+        self.position = 0;
+        self.tail_frames = None;
+
+        self.length
+    }
+
+    pub fn queue_overdub(&mut self) {
+        if self.state == Playing {
+            self.state = PlayingAwaitingOverdub;
+        }
+    }
+
+    pub fn set_feedback(&mut self, feedback: f32) {
+        self.feedback = feedback;
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.is_muted = !self.is_muted;
+    }
+
+    /// Silences the loop, without throwing it away. Returns false if there is no loop.
+    pub fn stop(&mut self) -> bool {
+        if !self.has_loop() {
+            return false;
+        }
+
+        self.state = Stopped;
+        true
+    }
+
+    /// Returns false if there is no loop, or it is already playing.
+    pub fn play(&mut self) -> bool {
+        if !matches!(self.state, Stopped | AwaitingOneShot | PlayingOnce) {
+            return false;
+        }
+
+        self.state = Playing;
+        true
+    }
+
+    /// Plays the loop through once, from the next time it comes round to the top. Returns false if
+    /// there is no loop.
+    pub fn one_shot(&mut self) -> bool {
+        if !self.has_loop() {
+            return false;
+        }
+
+        self.state = if self.position == 0 {
+            PlayingOnce
+        } else {
+            AwaitingOneShot
+        };
+        true
+    }
+
+    /// Doubles the length of the loop by repeating it, if there is space in the buffer. Overdubs
+    /// from before can no longer be undone. Returns false if the loop couldn't be multiplied.
+    pub fn multiply(&mut self) -> bool {
+        if !self.has_loop() || self.length * 2 > self.max_frames() {
+            return false;
+        }
+
+        let length = self.length * self.channels;
+        self.buffer.copy_within(..length, length);
+        self.length *= 2;
+        self.history.clear();
+
+        true
+    }
+
+    pub fn speed(&self) -> Speed {
+        self.speed
+    }
+
+    pub fn status(&self, is_waiting: bool) -> TrackStatus {
+        TrackStatus {
+            state: self.state,
+            speed: self.speed,
+            is_muted: self.is_muted,
+            is_waiting,
+            length: self.length,
+            position: self.position / Self::SUBFRAMES,
+            undo_layers: self.history.undo_len(),
+            redo_layers: self.history.redo_len(),
+        }
+    }
+
+    /// Switches between the given speed and normal speed.
+    pub fn toggle_speed(&mut self, speed: Speed) {
+        self.speed = if self.speed == speed {
+            Speed::Normal
+        } else {
+            speed
+        };
+    }
+
+    /// The number of frames since the loop was last at the top.
+    pub fn frames_since_top(&self) -> usize {
+        self.position / self.speed.step()
+    }
+
+    /// Moves the loop back by the given number of frames.
+    pub fn rewind(&mut self, frames: usize) {
+        if self.length == 0 {
+            return;
+        }
+
+        let length = self.loop_subframes();
+        let distance = (frames * self.speed.step()) % length;
+        self.position = (self.position + length - distance) % length;
+    }
+
+    /// Fades the loop out, and throws it away once it is silent, so that stopping it doesn't
+    /// click. Returns true if it was cleared straight away.
+    pub fn fade_out_and_clear(&mut self) -> bool {
+        if self.has_loop() && self.envelope > 0.0 {
+            self.state = Clearing;
+            false
+        } else {
+            self.clear();
+            true
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.state = Off;
+        self.length = 0;
+        self.position = 0;
+        self.speed = Speed::Normal;
+        self.history.clear();
+        self.tail_frames = None;
+        self.envelope = 0.0;
+    }
+
+    /// Restores the loop from before the last overdub pass. Undoing during a pass cancels it.
+    /// Returns false if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        if !self.has_loop() {
+            return false;
+        }
+
+        let length = self.length * self.channels;
+        let is_undone = self.history.undo(&mut self.buffer[..length]);
+
+        if is_undone && self.state == Overdubbing {
+            self.state = Playing;
+        }
+
+        is_undone
+    }
+
+    /// Puts back the last overdub pass which was undone. Returns false if there was nothing to
+    /// redo.
+    pub fn redo(&mut self) -> bool {
+        if !self.has_loop() || self.state == Overdubbing {
+            return false;
+        }
+
+        let length = self.length * self.channels;
+        self.history.redo(&mut self.buffer[..length])
+    }
+
+    /// The index in the buffer of `channel` in `frame`. Frames are mirrored when the loop is
+    /// reversed, rather than samples, so the channels stay in place.
+    fn index(&self, frame: usize, channel: usize, reverse: bool) -> usize {
+        let frame = if reverse {
+            self.length - 1 - frame
+        } else {
+            frame
+        };
+
+        frame * self.channels + channel
+    }
+
+    /// Records and plays one frame, adding the loop's output into `output`. Returns the track's
+    /// new state if it changed.
+    pub fn process_frame(
+        &mut self,
+        input: &[f32],
+        output: &mut [f32],
+        reverse: bool,
+    ) -> Option<State> {
+        match self.state {
+            Off => None,
+            Recording => {
+                let frame = self.position / Self::SUBFRAMES;
+                let gain = self.fade_in(frame);
+                let start = frame * self.channels;
+
+                for (sample, input) in self.buffer[start..start + self.channels]
+                    .iter_mut()
+                    .zip(input)
+                {
+                    *sample = input * gain;
+                }
+                self.position += Self::SUBFRAMES;
+
+                if self.position / Self::SUBFRAMES == self.max_frames() {
+                    Some(Playing)
+                } else {
+                    None
+                }
+            }
+            Stopped | AwaitingOneShot => {
+                // finish fading out
+                if self.envelope > 0.0 {
+                    self.play_frame(input, output, reverse);
+                }
+                self.advance()
+            }
+            Clearing => {
+                self.play_frame(input, output, reverse);
+
+                if self.envelope > 0.0 {
+                    self.advance();
+                    None
+                } else {
+                    self.clear();
+                    Some(Off)
+                }
+            }
+            Playing | PlayingAwaitingOverdub | Overdubbing | PlayingOnce => {
+                self.play_frame(input, output, reverse);
+                self.advance()
+            }
+        }
+    }
+
+    fn play_frame(&mut self, input: &[f32], output: &mut [f32], reverse: bool) {
+        let target = if self.is_playing() && !self.is_muted {
+            1.0
+        } else {
+            0.0
+        };
+        self.envelope = if target > self.envelope {
+            (self.envelope + self.fade_step).min(target)
+        } else {
+            (self.envelope - self.fade_step).max(target)
+        };
+        let gain = self.level * self.envelope;
+
+        let frame = self.position / Self::SUBFRAMES;
+        let next_frame = (frame + 1) % self.length;
+        // half speed lands between frames every other frame
+        let fraction = (self.position % Self::SUBFRAMES) as f32 / Self::SUBFRAMES as f32;
+        // at half speed, each frame of the loop is overdubbed twice
+        let overdub_gain = (self.speed.step() as f32 / Self::SUBFRAMES as f32).min(1.0);
+        let layer_gain = if self.state == Overdubbing {
+            self.fade_in(self.layer_frames)
+        } else {
+            0.0
+        };
+        let tail_gain = self
+            .tail_frames
+            .map_or(0.0, |tail_frames| self.fade_out(tail_frames));
+        // at half speed, only fade each frame of the loop once
+        let decay = if fraction == 0.0 {
+            self.feedback.powf(self.decay_weight())
+        } else {
+            1.0
+        };
+
+        for channel in 0..self.channels {
+            let index = self.index(frame, channel, reverse);
+            let next_index = self.index(next_frame, channel, reverse);
+            let sample = self.buffer[index] * (1.0 - fraction) + self.buffer[next_index] * fraction;
+            output[channel] += sample * gain;
+
+            // overdubs and tails line up with what is heard, so they play backwards once the loop is
+            // turned around
+            self.buffer[index] = self.buffer[index] * decay
+                + input[channel] * overdub_gain * (layer_gain + tail_gain);
+        }
+
+        self.layer_frames += 1;
+        self.tail_frames = self
+            .tail_frames
+            .map(|tail_frames| tail_frames + 1)
+            .filter(|tail_frames| *tail_frames < self.crossfade);
+    }
+
+    fn advance(&mut self) -> Option<State> {
+        self.position += self.speed.step();
+
+        if self.position >= self.loop_subframes() {
+            // a step can be longer than a very short loop, so it may wrap more than once
+            self.position %= self.loop_subframes();
+            self.wrap()
+        } else {
+            None
+        }
+    }
+
+    fn wrap(&mut self) -> Option<State> {
+        self.state = match self.state {
+            PlayingAwaitingOverdub => {
+                let length = self.length * self.channels;
+                self.history.record(&self.buffer[..length]);
+                self.layer_frames = 0;
+                Overdubbing
+            }
+            Overdubbing => {
+                self.start_tail();
+                Playing
+            }
+            AwaitingOneShot => PlayingOnce,
+            PlayingOnce => Stopped,
+            _ => return None,
+        };
+
+        Some(self.state)
+    }
+}
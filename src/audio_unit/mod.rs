@@ -1,19 +1,65 @@
+pub mod biquad;
+pub mod bitcrusher;
+pub mod compressor;
 pub mod delay;
+pub mod drive;
+pub mod freeze;
+pub mod gate;
+pub mod granular;
+pub mod lfo;
+pub mod limiter;
 pub mod looper;
+pub mod modulated_delay;
+pub mod one_pole;
+pub mod pan;
+pub mod phaser;
+pub mod pitch_shifter;
+pub mod ring_modulator;
+pub mod sample_rate_reducer;
+pub mod tape_echo;
+pub mod tremolo;
+pub mod wah;
 
+mod delay_line;
+mod envelope_follower;
 mod fft;
 mod gain;
+mod oversampler;
 mod pipeline;
+mod pitch_detector;
 mod split;
 mod transparent;
 
+pub use biquad::Biquad;
+pub use bitcrusher::Bitcrusher;
+pub use compressor::Compressor;
 pub use delay::Delay;
+pub use delay_line::DelayLine;
+pub use drive::Drive;
+pub use envelope_follower::EnvelopeFollower;
 pub use fft::Fft;
+pub use freeze::Freeze;
 pub use gain::Gain;
+pub use gate::Gate;
+pub use granular::Granular;
+pub use lfo::Lfo;
+pub use limiter::Limiter;
 pub use looper::Looper;
+pub use modulated_delay::ModulatedDelay;
+pub use one_pole::OnePole;
+pub use oversampler::Oversampler;
+pub use pan::Pan;
+pub use phaser::Phaser;
 pub use pipeline::Pipeline;
+pub use pitch_detector::PitchDetector;
+pub use pitch_shifter::PitchShifter;
+pub use ring_modulator::RingModulator;
+pub use sample_rate_reducer::SampleRateReducer;
 pub use split::Split;
+pub use tape_echo::TapeEcho;
 pub use transparent::Transparent;
+pub use tremolo::Tremolo;
+pub use wah::Wah;
 
 use crate::Result;
 
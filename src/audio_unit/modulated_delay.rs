@@ -0,0 +1,112 @@
+use crate::{
+    audio_unit::{lfo, AudioUnit, DelayLine, Lfo},
+    Result,
+};
+use cpal::StreamConfig;
+use std::sync::mpsc::{self, Receiver, Sender};
+use Message::*;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Parameters {
+    pub shape: lfo::Shape,
+    pub rate_hz: f32,
+    /// The shortest delay, reached at the bottom of the LFO's cycle.
+    pub delay_ms: f32,
+    /// How much the LFO lengthens the delay at the top of its cycle.
+    pub depth_ms: f32,
+    pub feedback: f32,
+    pub mix: f32,
+    /// How far apart the LFO's phase is on neighbouring channels, in cycles.
+    pub stereo_phase: f32,
+}
+
+#[derive(Debug)]
+pub enum Message {
+    SetRate(f32),
+    SetDepth(f32),
+    SetFeedback(f32),
+    ResetPhase,
+}
+
+/// A delay whose length is swept by an LFO. Short delays mixed with the dry signal give chorus
+/// and flanging, and a fully wet signal gives vibrato.
+pub struct ModulatedDelay {
+    parameters: Parameters,
+    samples_per_ms: f32,
+    lfo: Lfo,
+    delay_lines: Vec<DelayLine>,
+    messages: Receiver<Message>,
+}
+
+impl ModulatedDelay {
+    pub fn new(
+        stream_config: &StreamConfig,
+        parameters: Parameters,
+        max_delay_ms: f32,
+    ) -> (Self, Sender<Message>) {
+        let (sender, receiver) = mpsc::channel();
+        let sample_rate = stream_config.sample_rate.0 as f32;
+        let samples_per_ms = sample_rate / 1_000.0;
+        let max_delay_samples = (max_delay_ms * samples_per_ms).ceil() as usize;
+
+        (
+            Self {
+                parameters,
+                samples_per_ms,
+                lfo: Lfo::new(sample_rate, parameters.shape, parameters.rate_hz),
+                delay_lines: (0..stream_config.channels)
+                    .map(|_| DelayLine::new(max_delay_samples))
+                    .collect(),
+                messages: receiver,
+            },
+            sender,
+        )
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        for message in messages {
+            match message {
+                SetRate(rate_hz) => {
+                    self.parameters.rate_hz = rate_hz;
+                    self.lfo.set_frequency(rate_hz);
+                }
+                SetDepth(depth_ms) => self.parameters.depth_ms = depth_ms,
+                SetFeedback(feedback) => self.parameters.feedback = feedback,
+                ResetPhase => self.lfo.reset(),
+            }
+        }
+    }
+}
+
+impl AudioUnit for ModulatedDelay {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_messages();
+
+        let channels = self.delay_lines.len();
+        let Parameters {
+            delay_ms,
+            depth_ms,
+            feedback,
+            mix,
+            stereo_phase,
+            ..
+        } = self.parameters;
+
+        for (input, output) in input.chunks(channels).zip(output.chunks_mut(channels)) {
+            for (channel, delay_line) in self.delay_lines.iter_mut().enumerate() {
+                let modulation = self.lfo.value_with_offset(channel as f32 * stereo_phase);
+                let delay = delay_ms + depth_ms * (modulation + 1.0) / 2.0;
+
+                let delayed = delay_line.read(delay * self.samples_per_ms);
+                delay_line.write(input[channel] + delayed * feedback);
+
+                output[channel] = input[channel] * (1.0 - mix) + delayed * mix;
+            }
+
+            self.lfo.advance();
+        }
+
+        Ok(())
+    }
+}
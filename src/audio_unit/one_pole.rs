@@ -0,0 +1,85 @@
+use crate::{audio_unit::AudioUnit, Result};
+use cpal::StreamConfig;
+use std::{
+    f32::consts::PI,
+    sync::mpsc::{self, Receiver, Sender},
+};
+
+#[derive(Copy, Clone, Debug)]
+pub enum Kind {
+    LowPass,
+    HighPass,
+}
+
+#[derive(Debug)]
+pub enum Message {
+    SetFrequency(f32),
+}
+
+/// A 6dB/octave filter, cheap enough to use for tone controls and DC blocking.
+pub struct OnePole {
+    kind: Kind,
+    sample_rate: f32,
+    coefficient: f32,
+    // one low pass state per channel, since samples are interleaved
+    states: Vec<f32>,
+    messages: Receiver<Message>,
+}
+
+impl OnePole {
+    pub fn new(
+        stream_config: &StreamConfig,
+        kind: Kind,
+        frequency: f32,
+    ) -> (Self, Sender<Message>) {
+        let (sender, receiver) = mpsc::channel();
+        let sample_rate = stream_config.sample_rate.0 as f32;
+
+        (
+            Self {
+                kind,
+                sample_rate,
+                coefficient: Self::coefficient(sample_rate, frequency),
+                states: vec![0.0; stream_config.channels as usize],
+                messages: receiver,
+            },
+            sender,
+        )
+    }
+
+    fn coefficient(sample_rate: f32, frequency: f32) -> f32 {
+        let frequency = frequency.clamp(1.0, sample_rate / 2.0);
+        1.0 - (-2.0 * PI * frequency / sample_rate).exp()
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        for message in messages {
+            match message {
+                Message::SetFrequency(frequency) => {
+                    self.coefficient = Self::coefficient(self.sample_rate, frequency)
+                }
+            }
+        }
+    }
+}
+
+impl AudioUnit for OnePole {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_messages();
+
+        let channels = self.states.len();
+
+        for (i, sample) in input.iter().enumerate() {
+            let state = &mut self.states[i % channels];
+            *state += self.coefficient * (sample - *state);
+
+            output[i] = match self.kind {
+                Kind::LowPass => *state,
+                Kind::HighPass => sample - *state,
+            };
+        }
+
+        Ok(())
+    }
+}
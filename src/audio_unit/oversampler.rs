@@ -0,0 +1,159 @@
+use std::f32::consts::PI;
+
+/// Runs a nonlinear function at a multiple of the stream's sample rate, so that the harmonics it
+/// generates above the original Nyquist frequency are filtered out instead of aliasing.
+pub struct Oversampler {
+    factor: usize,
+    kernel: Vec<f32>,
+    channels: Vec<Channel>,
+}
+
+struct Channel {
+    upsampled: History,
+    processed: History,
+}
+
+/// The most recent samples fed into a FIR filter, stored as a ring.
+struct History {
+    samples: Vec<f32>,
+    position: usize,
+}
+
+impl History {
+    fn new(length: usize) -> Self {
+        Self {
+            samples: vec![0.0; length],
+            position: 0,
+        }
+    }
+
+    fn push(&mut self, sample: f32) {
+        self.position = (self.position + 1) % self.samples.len();
+        self.samples[self.position] = sample;
+    }
+
+    fn convolve(&self, kernel: &[f32]) -> f32 {
+        let length = self.samples.len();
+
+        kernel
+            .iter()
+            .enumerate()
+            .map(|(i, coefficient)| {
+                coefficient * self.samples[(self.position + length - i) % length]
+            })
+            .sum()
+    }
+}
+
+impl Oversampler {
+    const TAPS_PER_FACTOR: usize = 16;
+
+    pub fn new(factor: usize, channels: usize) -> Self {
+        let kernel = Self::kernel(factor);
+
+        let channels = (0..channels)
+            .map(|_| Channel {
+                upsampled: History::new(kernel.len()),
+                processed: History::new(kernel.len()),
+            })
+            .collect();
+
+        Self {
+            factor,
+            kernel,
+            channels,
+        }
+    }
+
+    /// A Blackman-windowed sinc low pass filter with its cutoff at the original Nyquist frequency.
+    fn kernel(factor: usize) -> Vec<f32> {
+        if factor <= 1 {
+            return vec![1.0];
+        }
+
+        let length = Self::TAPS_PER_FACTOR * factor;
+        let center = (length - 1) as f32 / 2.0;
+        let cutoff = 0.5 / factor as f32;
+
+        let kernel: Vec<f32> = (0..length)
+            .map(|i| {
+                let n = i as f32 - center;
+                let sinc = if n == 0.0 {
+                    2.0 * cutoff
+                } else {
+                    (2.0 * PI * cutoff * n).sin() / (PI * n)
+                };
+                let phase = 2.0 * PI * i as f32 / (length - 1) as f32;
+                let window = 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos();
+
+                sinc * window
+            })
+            .collect();
+
+        let sum: f32 = kernel.iter().sum();
+        kernel.iter().map(|coefficient| coefficient / sum).collect()
+    }
+
+    pub fn process<F>(&mut self, input: &[f32], output: &mut [f32], mut function: F)
+    where
+        F: FnMut(f32) -> f32,
+    {
+        if self.factor <= 1 {
+            for (i, sample) in input.iter().enumerate() {
+                output[i] = function(*sample);
+            }
+
+            return;
+        }
+
+        let num_channels = self.channels.len();
+
+        for (i, sample) in input.iter().enumerate() {
+            let channel = &mut self.channels[i % num_channels];
+
+            for step in 0..self.factor {
+                // zero-stuffing loses energy proportional to the factor, so make it up here
+                let stuffed = if step == 0 {
+                    sample * self.factor as f32
+                } else {
+                    0.0
+                };
+
+                channel.upsampled.push(stuffed);
+                let interpolated = channel.upsampled.convolve(&self.kernel);
+                channel.processed.push(function(interpolated));
+            }
+
+            output[i] = channel.processed.convolve(&self.kernel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_preserves_dc() {
+        let mut oversampler = Oversampler::new(4, 1);
+        let input = vec![1.0; 256];
+        let mut output = vec![0.0; 256];
+
+        oversampler.process(&input, &mut output, |sample| sample);
+
+        // once the filters have settled, a constant signal should pass through at unity gain
+        for sample in &output[128..] {
+            assert!((sample - 1.0).abs() < 0.01, "{}", sample);
+        }
+    }
+
+    #[test]
+    fn test_process_without_oversampling() {
+        let mut oversampler = Oversampler::new(1, 2);
+        let mut output = vec![0.0; 4];
+
+        oversampler.process(&[0.5, -0.5, 2.0, -2.0], &mut output, |sample| sample * 2.0);
+
+        assert_eq!(output, vec![1.0, -1.0, 4.0, -4.0]);
+    }
+}
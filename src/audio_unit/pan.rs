@@ -0,0 +1,68 @@
+use crate::{audio_unit::AudioUnit, Result};
+use cpal::StreamConfig;
+use std::f32::consts::{FRAC_PI_4, SQRT_2};
+
+/// The left and right gains for a position between -1 (left) and 1 (right). The panning is equal
+/// power, and scaled so that the center position is at unity gain.
+pub fn gains(position: f32) -> (f32, f32) {
+    let angle = (position.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4;
+    (angle.cos() * SQRT_2, angle.sin() * SQRT_2)
+}
+
+/// Places the input in the stereo field. The channels are mixed together first, so that a source
+/// which only arrives on one channel can still be moved to the other. With fewer than two
+/// channels, the input passes through unchanged.
+pub struct Pan {
+    left: f32,
+    right: f32,
+    channels: usize,
+}
+
+impl Pan {
+    pub fn new(stream_config: &StreamConfig, position: f32) -> Self {
+        let (left, right) = gains(position);
+
+        Self {
+            left,
+            right,
+            channels: stream_config.channels as usize,
+        }
+    }
+}
+
+impl AudioUnit for Pan {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        if self.channels < 2 {
+            output.copy_from_slice(input);
+            return Ok(());
+        }
+
+        for (input, output) in input
+            .chunks(self.channels)
+            .zip(output.chunks_mut(self.channels))
+        {
+            let mono = (input[0] + input[1]) / 2.0;
+            output[0] = mono * self.left;
+            output[1] = mono * self.right;
+            output[2..].copy_from_slice(&input[2..]);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gains() {
+        let (left, right) = gains(0.0);
+        assert!((left - 1.0).abs() < 1e-6);
+        assert!((right - 1.0).abs() < 1e-6);
+
+        let (left, right) = gains(-1.0);
+        assert!((left - SQRT_2).abs() < 1e-6);
+        assert!(right.abs() < 1e-6);
+    }
+}
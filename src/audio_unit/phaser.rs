@@ -0,0 +1,128 @@
+use crate::{
+    audio_unit::{biquad, lfo, AudioUnit, Lfo},
+    Result,
+};
+use cpal::StreamConfig;
+use std::sync::mpsc::{self, Receiver, Sender};
+use Message::*;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Parameters {
+    pub shape: lfo::Shape,
+    pub rate_hz: f32,
+    /// How far the sweep moves either side of the center frequency, in octaves.
+    pub depth: f32,
+    pub feedback: f32,
+    pub center_hz: f32,
+    pub mix: f32,
+}
+
+#[derive(Debug)]
+pub enum Message {
+    SetRate(f32),
+    SetDepth(f32),
+    SetFeedback(f32),
+    SetCenter(f32),
+    ResetPhase,
+}
+
+#[derive(Clone)]
+struct Channel {
+    sections: Vec<biquad::State>,
+    last_output: f32,
+}
+
+/// Sweeps a chain of all-pass filters, and mixes the result with the dry signal so that the
+/// phase shifts become moving notches. Each second-order all-pass section counts as two stages.
+pub struct Phaser {
+    parameters: Parameters,
+    sample_rate: f32,
+    lfo: Lfo,
+    channels: Vec<Channel>,
+    messages: Receiver<Message>,
+}
+
+impl Phaser {
+    const SECTION_Q: f32 = 0.5;
+
+    pub fn new(
+        stream_config: &StreamConfig,
+        parameters: Parameters,
+        stages: usize,
+    ) -> (Self, Sender<Message>) {
+        let (sender, receiver) = mpsc::channel();
+        let sample_rate = stream_config.sample_rate.0 as f32;
+
+        let channel = Channel {
+            sections: vec![biquad::State::default(); stages / 2],
+            last_output: 0.0,
+        };
+
+        (
+            Self {
+                parameters,
+                sample_rate,
+                lfo: Lfo::new(sample_rate, parameters.shape, parameters.rate_hz),
+                channels: vec![channel; stream_config.channels as usize],
+                messages: receiver,
+            },
+            sender,
+        )
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        for message in messages {
+            match message {
+                SetRate(rate_hz) => {
+                    self.parameters.rate_hz = rate_hz;
+                    self.lfo.set_frequency(rate_hz);
+                }
+                SetDepth(depth) => self.parameters.depth = depth,
+                SetFeedback(feedback) => self.parameters.feedback = feedback,
+                SetCenter(center_hz) => self.parameters.center_hz = center_hz,
+                ResetPhase => self.lfo.reset(),
+            }
+        }
+    }
+
+    fn coefficients(&self) -> biquad::Coefficients {
+        let frequency =
+            self.parameters.center_hz * 2.0_f32.powf(self.parameters.depth * self.lfo.value());
+        let parameters =
+            biquad::Parameters::new(biquad::Kind::AllPass, frequency, Self::SECTION_Q, 0.0);
+
+        biquad::Coefficients::new(&parameters, self.sample_rate)
+    }
+}
+
+impl AudioUnit for Phaser {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_messages();
+
+        let num_channels = self.channels.len();
+        let Parameters { feedback, mix, .. } = self.parameters;
+
+        for (input, output) in input
+            .chunks(num_channels)
+            .zip(output.chunks_mut(num_channels))
+        {
+            let coefficients = self.coefficients();
+
+            for (channel, state) in self.channels.iter_mut().enumerate() {
+                let mut wet = input[channel] + state.last_output * feedback;
+
+                for section in &mut state.sections {
+                    wet = section.process(&coefficients, wet);
+                }
+
+                state.last_output = wet;
+                output[channel] = input[channel] * (1.0 - mix) + wet * mix;
+            }
+
+            self.lfo.advance();
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+/// Estimates the fundamental frequency of a window of samples, using the YIN algorithm.
+///
+/// See de Cheveigné and Kawahara, "YIN, a fundamental frequency estimator for speech and music".
+pub struct PitchDetector {
+    sample_rate: f32,
+    threshold: f32,
+    difference: Vec<f32>,
+}
+
+impl PitchDetector {
+    /// Lower thresholds reject more noisy windows, but may also miss quiet or breathy notes.
+    pub const DEFAULT_THRESHOLD: f32 = 0.15;
+    // windows quieter than this (in RMS) are treated as silence
+    const MIN_LEVEL: f32 = 0.001;
+
+    /// `window_size` is the number of samples which will be passed to `detect`. The lowest
+    /// detectable frequency is `sample_rate / (window_size / 2)`.
+    pub fn new(sample_rate: f32, window_size: usize, threshold: f32) -> Self {
+        Self {
+            sample_rate,
+            threshold,
+            difference: vec![0.0; window_size / 2],
+        }
+    }
+
+    /// The frequency of `samples` in Hz, or `None` if they are too quiet or have no clear pitch.
+    pub fn detect(&mut self, samples: &[f32]) -> Option<f32> {
+        let max_lag = self.difference.len().min(samples.len() / 2);
+        let level = (samples.iter().map(|sample| sample * sample).sum::<f32>()
+            / samples.len() as f32)
+            .sqrt();
+
+        if max_lag < 3 || level < Self::MIN_LEVEL {
+            return None;
+        }
+
+        self.compute_difference(samples, max_lag);
+        self.normalize(max_lag);
+
+        let lag = self.first_dip(max_lag)?;
+        Some(self.sample_rate / self.interpolate(lag, max_lag))
+    }
+
+    /// How much the window differs from itself shifted by each lag.
+    fn compute_difference(&mut self, samples: &[f32], max_lag: usize) {
+        for lag in 0..max_lag {
+            self.difference[lag] = (0..max_lag)
+                .map(|i| {
+                    let delta = samples[i] - samples[i + lag];
+                    delta * delta
+                })
+                .sum();
+        }
+    }
+
+    /// Divides each difference by the average of the differences at shorter lags, so that the
+    /// small lags near zero don't look like periods.
+    fn normalize(&mut self, max_lag: usize) {
+        let mut running_sum = 0.0;
+        self.difference[0] = 1.0;
+
+        for lag in 1..max_lag {
+            running_sum += self.difference[lag];
+            self.difference[lag] = if running_sum > 0.0 {
+                self.difference[lag] * lag as f32 / running_sum
+            } else {
+                1.0
+            };
+        }
+    }
+
+    /// The first lag which dips below the threshold, followed down to the bottom of its dip.
+    fn first_dip(&self, max_lag: usize) -> Option<usize> {
+        let mut lag = (2..max_lag).find(|lag| self.difference[*lag] < self.threshold)?;
+
+        while lag + 1 < max_lag && self.difference[lag + 1] < self.difference[lag] {
+            lag += 1;
+        }
+
+        Some(lag)
+    }
+
+    /// Refines a lag to a fractional value by fitting a parabola through its neighbours.
+    fn interpolate(&self, lag: usize, max_lag: usize) -> f32 {
+        if lag + 1 >= max_lag {
+            return lag as f32;
+        }
+
+        let previous = self.difference[lag - 1];
+        let current = self.difference[lag];
+        let next = self.difference[lag + 1];
+        let curvature = previous - 2.0 * current + next;
+
+        if curvature.abs() < f32::EPSILON {
+            lag as f32
+        } else {
+            lag as f32 + 0.5 * (previous - next) / curvature
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const WINDOW_SIZE: usize = 2048;
+
+    fn sine(frequency: f32) -> Vec<f32> {
+        (0..WINDOW_SIZE)
+            .map(|i| 0.5 * (2.0 * PI * frequency * i as f32 / SAMPLE_RATE).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_detects_sine() {
+        let mut detector =
+            PitchDetector::new(SAMPLE_RATE, WINDOW_SIZE, PitchDetector::DEFAULT_THRESHOLD);
+
+        for frequency in [82.41, 110.0, 440.0, 1_318.5] {
+            let detected = detector.detect(&sine(frequency)).unwrap();
+            assert!(
+                (detected - frequency).abs() < frequency * 0.002,
+                "expected {}, detected {}",
+                frequency,
+                detected
+            );
+        }
+    }
+
+    #[test]
+    fn test_silence() {
+        let mut detector =
+            PitchDetector::new(SAMPLE_RATE, WINDOW_SIZE, PitchDetector::DEFAULT_THRESHOLD);
+
+        assert_eq!(detector.detect(&[0.0; WINDOW_SIZE]), None);
+    }
+}
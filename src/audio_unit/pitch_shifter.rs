@@ -0,0 +1,62 @@
+use crate::{
+    audio_unit::{AudioUnit, DelayLine},
+    Result,
+};
+use cpal::StreamConfig;
+use std::f32::consts::PI;
+
+/// A granular, time-domain pitch shifter. Two read heads sweep through a short delay line at a
+/// speed set by the pitch ratio, half a window apart, and each fades out as it jumps back to the
+/// start of the window so that the other covers the discontinuity.
+pub struct PitchShifter {
+    ratio: f32,
+    window: f32,
+    phase: f32,
+    delay_lines: Vec<DelayLine>,
+}
+
+impl PitchShifter {
+    pub fn new(stream_config: &StreamConfig, semitones: f32, window_ms: f32) -> Self {
+        let window = window_ms / 1_000.0 * stream_config.sample_rate.0 as f32;
+
+        Self {
+            ratio: Self::ratio(semitones),
+            window,
+            phase: 0.0,
+            delay_lines: (0..stream_config.channels)
+                .map(|_| DelayLine::new(window.ceil() as usize))
+                .collect(),
+        }
+    }
+
+    pub fn ratio(semitones: f32) -> f32 {
+        2.0_f32.powf(semitones / 12.0)
+    }
+}
+
+impl AudioUnit for PitchShifter {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        let channels = self.delay_lines.len();
+        let window = self.window;
+        // reading faster than writing shortens the delay, and vice versa
+        let phase_increment = (1.0 - self.ratio) / self.window;
+
+        for (input, output) in input.chunks(channels).zip(output.chunks_mut(channels)) {
+            let heads = [self.phase, (self.phase + 0.5).fract()];
+
+            for (channel, delay_line) in self.delay_lines.iter_mut().enumerate() {
+                delay_line.write(input[channel]);
+
+                // the two heads' gains are sin² and cos² of the same angle, so they sum to one
+                output[channel] = heads
+                    .iter()
+                    .map(|head| delay_line.read(head * window) * (PI * head).sin().powi(2))
+                    .sum();
+            }
+
+            self.phase = (self.phase + phase_increment).rem_euclid(1.0);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{
+    audio_unit::{lfo, AudioUnit, Lfo},
+    Result,
+};
+use cpal::StreamConfig;
+use std::sync::mpsc::{self, Receiver, Sender};
+use Message::*;
+
+#[derive(Debug)]
+pub enum Message {
+    SetFrequency(f32),
+    SetMix(f32),
+}
+
+/// Multiplies the input by a carrier wave. The output contains the sums and differences of the
+/// input's frequencies and the carrier's, which sound metallic and inharmonic.
+pub struct RingModulator {
+    carrier: Lfo,
+    mix: f32,
+    channels: usize,
+    messages: Receiver<Message>,
+}
+
+impl RingModulator {
+    pub fn new(
+        stream_config: &StreamConfig,
+        shape: lfo::Shape,
+        frequency: f32,
+        mix: f32,
+    ) -> (Self, Sender<Message>) {
+        let (sender, receiver) = mpsc::channel();
+
+        (
+            Self {
+                carrier: Lfo::new(stream_config.sample_rate.0 as f32, shape, frequency),
+                mix,
+                channels: stream_config.channels as usize,
+                messages: receiver,
+            },
+            sender,
+        )
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        for message in messages {
+            match message {
+                SetFrequency(frequency) => self.carrier.set_frequency(frequency),
+                SetMix(mix) => self.mix = mix,
+            }
+        }
+    }
+}
+
+impl AudioUnit for RingModulator {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_messages();
+
+        let mix = self.mix;
+
+        for (input, output) in input
+            .chunks(self.channels)
+            .zip(output.chunks_mut(self.channels))
+        {
+            let carrier = self.carrier.value();
+
+            for (channel, sample) in input.iter().enumerate() {
+                output[channel] = sample * (1.0 - mix) + sample * carrier * mix;
+            }
+
+            self.carrier.advance();
+        }
+
+        Ok(())
+    }
+}
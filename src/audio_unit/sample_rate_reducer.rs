@@ -0,0 +1,89 @@
+use crate::{audio_unit::AudioUnit, Result};
+use cpal::StreamConfig;
+use std::sync::mpsc::{self, Receiver, Sender};
+use Message::*;
+
+#[derive(Debug)]
+pub enum Message {
+    SetRate(f32),
+}
+
+/// Samples the input at a lower rate, and holds each sample until the next one is taken. Nothing
+/// filters the steps, so frequencies above the new rate fold back down as aliasing.
+pub struct SampleRateReducer {
+    rate_hz: f32,
+    sample_rate: f32,
+    phase: f32,
+    held: Vec<f32>,
+    messages: Receiver<Message>,
+}
+
+impl SampleRateReducer {
+    pub fn new(stream_config: &StreamConfig, rate_hz: f32) -> (Self, Sender<Message>) {
+        let (sender, receiver) = mpsc::channel();
+
+        (
+            Self {
+                rate_hz,
+                sample_rate: stream_config.sample_rate.0 as f32,
+                // start at the end of a period, so that the first frame is sampled
+                phase: 1.0,
+                held: vec![0.0; stream_config.channels as usize],
+                messages: receiver,
+            },
+            sender,
+        )
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        for message in messages {
+            match message {
+                SetRate(rate_hz) => self.rate_hz = rate_hz,
+            }
+        }
+    }
+}
+
+impl AudioUnit for SampleRateReducer {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_messages();
+
+        let channels = self.held.len();
+        let increment = (self.rate_hz / self.sample_rate).min(1.0);
+
+        for (input, output) in input.chunks(channels).zip(output.chunks_mut(channels)) {
+            if self.phase >= 1.0 {
+                self.phase -= 1.0;
+                self.held.copy_from_slice(input);
+            }
+
+            output.copy_from_slice(&self.held);
+            self.phase += increment;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cpal::SampleRate;
+
+    #[test]
+    fn test_holds_samples() {
+        let stream_config = StreamConfig {
+            channels: 1,
+            sample_rate: SampleRate(4),
+            buffer_size: cpal::BufferSize::Default,
+        };
+        let (mut reducer, _) = SampleRateReducer::new(&stream_config, 2.0);
+        let input = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let mut output = [0.0; 5];
+
+        reducer.process(&input, &mut output).unwrap();
+
+        assert_eq!(output, [1.0, 1.0, 3.0, 3.0, 5.0]);
+    }
+}
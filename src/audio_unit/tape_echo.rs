@@ -0,0 +1,224 @@
+use crate::{
+    audio_unit::{biquad, drive, lfo, AudioUnit, DelayLine, EnvelopeFollower, Lfo},
+    Result,
+};
+use cpal::StreamConfig;
+use std::sync::mpsc::{self, Receiver, Sender};
+use Message::*;
+
+#[derive(Clone, Debug)]
+pub struct Parameters {
+    /// The distance to the first playback head.
+    pub delay_ms: f32,
+    /// The distance to each playback head, as a multiple of `delay_ms`.
+    pub heads: Vec<f32>,
+    /// The indices of the heads which are heard.
+    pub active_heads: Vec<usize>,
+    pub feedback: f32,
+    /// How hard the repeats are driven into the tape on each pass.
+    pub saturation: f32,
+    /// The cutoff of the low pass filter in the feedback path.
+    pub tone_hz: f32,
+    pub wow_rate_hz: f32,
+    pub wow_depth_ms: f32,
+    pub flutter_rate_hz: f32,
+    pub flutter_depth_ms: f32,
+}
+
+impl Parameters {
+    /// The longest delay which the heads and modulation can reach.
+    pub fn max_delay_ms(&self, max_delay_ms: f32) -> f32 {
+        let furthest_head = self.heads.iter().copied().fold(1.0, f32::max);
+        (max_delay_ms + 2.0 * (self.wow_depth_ms + self.flutter_depth_ms)) * furthest_head
+    }
+}
+
+#[derive(Debug)]
+pub enum Message {
+    SetDelay(f32),
+    SetActiveHeads(Vec<usize>),
+}
+
+#[derive(Clone, Default)]
+struct Channel {
+    tone: biquad::State,
+    low_cut: biquad::State,
+}
+
+/// An emulation of a tape echo. The repeats are read from several playback heads, and fed back
+/// onto the tape through a saturation stage and filters, so that each pass is darker and dirtier
+/// than the last. Slow (wow) and fast (flutter) variations in tape speed wobble the pitch.
+pub struct TapeEcho {
+    parameters: Parameters,
+    samples_per_ms: f32,
+    // the delay glides to its new length, like a motor changing speed
+    delay: EnvelopeFollower,
+    wow: Lfo,
+    flutter: Lfo,
+    tone: biquad::Coefficients,
+    low_cut: biquad::Coefficients,
+    channels: Vec<Channel>,
+    delay_lines: Vec<DelayLine>,
+    messages: Receiver<Message>,
+}
+
+impl TapeEcho {
+    const SPEED_CHANGE_MS: f32 = 150.0;
+    const LOW_CUT_HZ: f32 = 60.0;
+    const SATURATION_SHAPE: drive::Shape = drive::Shape::Tube;
+
+    pub fn new(
+        stream_config: &StreamConfig,
+        parameters: Parameters,
+        max_delay_ms: f32,
+    ) -> (Self, Sender<Message>) {
+        let (sender, receiver) = mpsc::channel();
+        let sample_rate = stream_config.sample_rate.0 as f32;
+        let samples_per_ms = sample_rate / 1_000.0;
+        let max_delay_samples =
+            (parameters.max_delay_ms(max_delay_ms) * samples_per_ms).ceil() as usize;
+
+        let mut delay =
+            EnvelopeFollower::new(sample_rate, Self::SPEED_CHANGE_MS, Self::SPEED_CHANGE_MS);
+        // start at the configured length, rather than gliding up from zero
+        delay.reset(parameters.delay_ms);
+
+        let filter = |kind, frequency| {
+            let parameters =
+                biquad::Parameters::new(kind, frequency, biquad::Parameters::BUTTERWORTH_Q, 0.0);
+            biquad::Coefficients::new(&parameters, sample_rate)
+        };
+
+        (
+            Self {
+                samples_per_ms,
+                delay,
+                wow: Lfo::new(sample_rate, lfo::Shape::Sine, parameters.wow_rate_hz),
+                flutter: Lfo::new(sample_rate, lfo::Shape::Sine, parameters.flutter_rate_hz),
+                tone: filter(biquad::Kind::LowPass, parameters.tone_hz),
+                low_cut: filter(biquad::Kind::HighPass, Self::LOW_CUT_HZ),
+                channels: vec![Channel::default(); stream_config.channels as usize],
+                delay_lines: (0..stream_config.channels)
+                    .map(|_| DelayLine::new(max_delay_samples))
+                    .collect(),
+                parameters,
+                messages: receiver,
+            },
+            sender,
+        )
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        for message in messages {
+            match message {
+                SetDelay(delay_ms) => self.parameters.delay_ms = delay_ms,
+                SetActiveHeads(active_heads) => self.parameters.active_heads = active_heads,
+            }
+        }
+    }
+
+    /// The distance to the first head in samples, including the wobble in tape speed.
+    fn next_delay(&mut self) -> f32 {
+        let Parameters {
+            wow_depth_ms,
+            flutter_depth_ms,
+            ..
+        } = self.parameters;
+
+        // offset the modulation so that it only ever lengthens the delay
+        let modulation_ms = wow_depth_ms * (1.0 + self.wow.value())
+            + flutter_depth_ms * (1.0 + self.flutter.value());
+
+        self.wow.advance();
+        self.flutter.advance();
+
+        (self.delay.process(self.parameters.delay_ms) + modulation_ms) * self.samples_per_ms
+    }
+
+    fn saturate(&self, sample: f32) -> f32 {
+        let saturation = self.parameters.saturation;
+        Self::SATURATION_SHAPE.apply(sample * saturation) / saturation
+    }
+}
+
+impl AudioUnit for TapeEcho {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_messages();
+
+        let num_channels = self.channels.len();
+
+        for (input, output) in input
+            .chunks(num_channels)
+            .zip(output.chunks_mut(num_channels))
+        {
+            let delay = self.next_delay();
+
+            for channel in 0..num_channels {
+                let delay_line = &self.delay_lines[channel];
+                let echo: f32 = self
+                    .parameters
+                    .active_heads
+                    .iter()
+                    .filter_map(|index| self.parameters.heads.get(*index))
+                    // the delay line is written after it's read, so it's already a sample behind
+                    .map(|head| delay_line.read(delay * head - 1.0))
+                    .sum();
+
+                let saturated = self.saturate(echo * self.parameters.feedback);
+                let state = &mut self.channels[channel];
+                let filtered = state
+                    .low_cut
+                    .process(&self.low_cut, state.tone.process(&self.tone, saturated));
+
+                self.delay_lines[channel].write(input[channel] + filtered);
+                output[channel] = input[channel] + echo;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cpal::{BufferSize, SampleRate};
+
+    #[test]
+    fn test_heads() {
+        let stream_config = StreamConfig {
+            channels: 1,
+            sample_rate: SampleRate(1_000),
+            buffer_size: BufferSize::Default,
+        };
+        let parameters = Parameters {
+            delay_ms: 10.0,
+            heads: vec![1.0, 2.0, 3.0],
+            active_heads: vec![0, 2],
+            feedback: 0.0,
+            saturation: 1.0,
+            tone_hz: 400.0,
+            wow_rate_hz: 0.5,
+            wow_depth_ms: 0.0,
+            flutter_rate_hz: 6.0,
+            flutter_depth_ms: 0.0,
+        };
+        let (mut tape_echo, _) = TapeEcho::new(&stream_config, parameters, 10.0);
+
+        let mut input = vec![0.0; 40];
+        input[0] = 1.0;
+        let mut output = vec![0.0; 40];
+
+        tape_echo.process(&input, &mut output).unwrap();
+
+        // the dry impulse, then the first and third heads
+        let echoes: Vec<_> = output
+            .iter()
+            .enumerate()
+            .filter(|(_, sample)| **sample != 0.0)
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(echoes, vec![0, 10, 30]);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{
+    audio_unit::{biquad, lfo, pan, AudioUnit, Lfo},
+    Result,
+};
+use cpal::StreamConfig;
+use std::sync::mpsc::{self, Receiver, Sender};
+use Message::*;
+
+#[derive(Copy, Clone, Debug)]
+pub enum Kind {
+    /// Modulates the volume of the whole signal.
+    Amplitude,
+    /// Splits the signal at a crossover frequency, and modulates the low and high bands in
+    /// opposite phase.
+    Harmonic { crossover_hz: f32 },
+    /// Moves the signal between the first two channels. Mono streams get amplitude tremolo
+    /// instead.
+    Pan,
+}
+
+#[derive(Debug)]
+pub enum Message {
+    SetRate(f32),
+    SetDepth(f32),
+    ResetPhase,
+}
+
+/// A Linkwitz-Riley crossover for a single channel: each band is two Butterworth filters in
+/// series, so that the bands sum back to a flat response.
+#[derive(Default)]
+struct Crossover {
+    low: [biquad::State; 2],
+    high: [biquad::State; 2],
+}
+
+impl Crossover {
+    fn process(
+        &mut self,
+        low_pass: &biquad::Coefficients,
+        high_pass: &biquad::Coefficients,
+        sample: f32,
+    ) -> (f32, f32) {
+        let low = self.low[0].process(low_pass, sample);
+        let high = self.high[0].process(high_pass, sample);
+
+        (
+            self.low[1].process(low_pass, low),
+            self.high[1].process(high_pass, high),
+        )
+    }
+}
+
+pub struct Tremolo {
+    kind: Kind,
+    depth: f32,
+    channels: usize,
+    lfo: Lfo,
+    crossover_coefficients: Option<(biquad::Coefficients, biquad::Coefficients)>,
+    crossovers: Vec<Crossover>,
+    messages: Receiver<Message>,
+}
+
+impl Tremolo {
+    pub fn new(
+        stream_config: &StreamConfig,
+        kind: Kind,
+        shape: lfo::Shape,
+        rate_hz: f32,
+        depth: f32,
+    ) -> (Self, Sender<Message>) {
+        let (sender, receiver) = mpsc::channel();
+        let sample_rate = stream_config.sample_rate.0 as f32;
+        let channels = stream_config.channels as usize;
+
+        let crossover_coefficients = match kind {
+            Kind::Harmonic { crossover_hz } => {
+                let coefficients = |kind| {
+                    let parameters = biquad::Parameters::new(
+                        kind,
+                        crossover_hz,
+                        biquad::Parameters::BUTTERWORTH_Q,
+                        0.0,
+                    );
+                    biquad::Coefficients::new(&parameters, sample_rate)
+                };
+
+                Some((
+                    coefficients(biquad::Kind::LowPass),
+                    coefficients(biquad::Kind::HighPass),
+                ))
+            }
+            _ => None,
+        };
+
+        (
+            Self {
+                kind,
+                depth,
+                channels,
+                lfo: Lfo::new(sample_rate, shape, rate_hz),
+                crossover_coefficients,
+                crossovers: (0..channels).map(|_| Crossover::default()).collect(),
+                messages: receiver,
+            },
+            sender,
+        )
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        for message in messages {
+            match message {
+                SetRate(rate_hz) => self.lfo.set_frequency(rate_hz),
+                SetDepth(depth) => self.depth = depth,
+                ResetPhase => self.lfo.reset(),
+            }
+        }
+    }
+
+    /// The gain for an LFO value between -1 and 1: unity at the top of the cycle, and
+    /// `1 - depth` at the bottom.
+    fn gain(&self, modulation: f32) -> f32 {
+        1.0 - self.depth * (1.0 - modulation) / 2.0
+    }
+
+    fn process_frame(&mut self, modulation: f32, input: &[f32], output: &mut [f32]) {
+        match self.kind {
+            Kind::Pan if self.channels >= 2 => {
+                let (left, right) = pan::gains(self.depth * modulation);
+                output[0] = input[0] * left;
+                output[1] = input[1] * right;
+                output[2..].copy_from_slice(&input[2..]);
+            }
+            Kind::Harmonic { .. } => {
+                let (low_pass, high_pass) = self
+                    .crossover_coefficients
+                    .expect("harmonic tremolo is missing its crossover");
+                let low_gain = self.gain(modulation);
+                let high_gain = self.gain(-modulation);
+
+                for (channel, sample) in input.iter().enumerate() {
+                    let (low, high) =
+                        self.crossovers[channel].process(&low_pass, &high_pass, *sample);
+                    output[channel] = low * low_gain + high * high_gain;
+                }
+            }
+            _ => {
+                let gain = self.gain(modulation);
+
+                for (channel, sample) in input.iter().enumerate() {
+                    output[channel] = sample * gain;
+                }
+            }
+        }
+    }
+}
+
+impl AudioUnit for Tremolo {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_messages();
+
+        for (input, output) in input
+            .chunks(self.channels)
+            .zip(output.chunks_mut(self.channels))
+        {
+            let modulation = self.lfo.value();
+            self.process_frame(modulation, input, output);
+            self.lfo.advance();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cpal::{BufferSize, SampleRate};
+
+    fn tremolo(channels: u16, kind: Kind, depth: f32) -> Tremolo {
+        let stream_config = StreamConfig {
+            channels,
+            sample_rate: SampleRate(1_000),
+            buffer_size: BufferSize::Default,
+        };
+
+        Tremolo::new(&stream_config, kind, lfo::Shape::Sine, 10.0, depth).0
+    }
+
+    #[test]
+    fn test_amplitude_depth() {
+        let mut tremolo = tremolo(1, Kind::Amplitude, 0.5);
+        let mut output = vec![0.0; 1_000];
+        tremolo.process(&[1.0; 1_000], &mut output).unwrap();
+
+        let max = output.iter().cloned().fold(f32::MIN, f32::max);
+        let min = output.iter().cloned().fold(f32::MAX, f32::min);
+        assert!((max - 1.0).abs() < 1e-3, "max {}", max);
+        assert!((min - 0.5).abs() < 1e-3, "min {}", min);
+    }
+
+    #[test]
+    fn test_pan_is_constant_power() {
+        let mut tremolo = tremolo(2, Kind::Pan, 1.0);
+        let mut output = vec![0.0; 2_000];
+        tremolo.process(&[1.0; 2_000], &mut output).unwrap();
+
+        for frame in output.chunks(2) {
+            let power = frame[0].powi(2) + frame[1].powi(2);
+            assert!((power - 2.0).abs() < 1e-4, "power {}", power);
+        }
+
+        // the full depth reaches both sides
+        let left = output.iter().step_by(2).cloned().fold(f32::MAX, f32::min);
+        assert!(left < 1e-3, "left {}", left);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{
+    audio_unit::{biquad, AudioUnit, EnvelopeFollower},
+    Result,
+};
+use cpal::StreamConfig;
+use serde::Deserialize;
+use std::sync::mpsc::{self, Receiver, Sender};
+use Message::*;
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
+pub enum Mode {
+    /// The filter follows the level of the input.
+    Auto,
+    /// The filter follows `SetPosition` messages, eg. from an expression pedal.
+    Expression,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Parameters {
+    pub mode: Mode,
+    pub kind: biquad::Kind,
+    pub min_hz: f32,
+    pub max_hz: f32,
+    pub q: f32,
+    /// How much the envelope moves the filter in auto mode.
+    pub sensitivity: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+}
+
+#[derive(Debug)]
+pub enum Message {
+    /// Moves the filter to a position between 0 (`min_hz`) and 1 (`max_hz`).
+    SetPosition(f32),
+    SetSensitivity(f32),
+    SetQ(f32),
+}
+
+/// A resonant filter swept by the input's envelope or by an expression pedal.
+pub struct Wah {
+    parameters: Parameters,
+    sample_rate: f32,
+    channels: usize,
+    envelope: EnvelopeFollower,
+    target_position: f32,
+    // smooths out the steps between control changes
+    position: EnvelopeFollower,
+    states: Vec<biquad::State>,
+    messages: Receiver<Message>,
+}
+
+impl Wah {
+    const POSITION_SMOOTHING_MS: f32 = 10.0;
+
+    pub fn new(stream_config: &StreamConfig, parameters: Parameters) -> (Self, Sender<Message>) {
+        let (sender, receiver) = mpsc::channel();
+        let sample_rate = stream_config.sample_rate.0 as f32;
+        let channels = stream_config.channels as usize;
+
+        (
+            Self {
+                parameters,
+                sample_rate,
+                channels,
+                envelope: EnvelopeFollower::new(
+                    sample_rate,
+                    parameters.attack_ms,
+                    parameters.release_ms,
+                ),
+                target_position: 0.0,
+                position: EnvelopeFollower::new(
+                    sample_rate,
+                    Self::POSITION_SMOOTHING_MS,
+                    Self::POSITION_SMOOTHING_MS,
+                ),
+                states: vec![biquad::State::default(); channels],
+                messages: receiver,
+            },
+            sender,
+        )
+    }
+
+    fn process_messages(&mut self) {
+        let messages: Vec<_> = self.messages.try_iter().collect();
+        for message in messages {
+            match message {
+                SetPosition(position) => self.target_position = position.clamp(0.0, 1.0),
+                SetSensitivity(sensitivity) => self.parameters.sensitivity = sensitivity,
+                SetQ(q) => self.parameters.q = q,
+            }
+        }
+    }
+
+    fn next_position(&mut self, frame: &[f32]) -> f32 {
+        let target = match self.parameters.mode {
+            Mode::Auto => {
+                (self.envelope.process_frame(frame) * self.parameters.sensitivity).min(1.0)
+            }
+            Mode::Expression => self.target_position,
+        };
+
+        self.position.process(target)
+    }
+
+    fn coefficients(&self, position: f32) -> biquad::Coefficients {
+        let Parameters {
+            kind,
+            min_hz,
+            max_hz,
+            q,
+            ..
+        } = self.parameters;
+
+        // sweep exponentially, so that equal movements sound like equal changes in pitch
+        let frequency = min_hz * (max_hz / min_hz).powf(position);
+        let parameters = biquad::Parameters::new(kind, frequency, q, 0.0);
+
+        biquad::Coefficients::new(&parameters, self.sample_rate)
+    }
+}
+
+impl AudioUnit for Wah {
+    fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<()> {
+        self.process_messages();
+
+        for (input, output) in input
+            .chunks(self.channels)
+            .zip(output.chunks_mut(self.channels))
+        {
+            let position = self.next_position(input);
+            let coefficients = self.coefficients(position);
+
+            for (channel, sample) in input.iter().enumerate() {
+                output[channel] = self.states[channel].process(&coefficients, *sample);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use crate::config::MidiSlider;
+use serde::Deserialize;
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct BitcrusherConfig {
+    #[serde(default = "BitcrusherConfig::default_bits")]
+    pub bits: f32,
+    #[serde(default = "BitcrusherConfig::default_bits_min")]
+    pub min_bits: f32,
+    #[serde(default = "BitcrusherConfig::default_bits_max")]
+    pub max_bits: f32,
+    /// Adds a little noise before rounding, which trades the harsh, gated sound of low bit depths
+    /// for hiss.
+    #[serde(default)]
+    pub dither: bool,
+    pub bits_slider: Option<MidiSlider>,
+}
+
+impl BitcrusherConfig {
+    const DEFAULT_BITS: f32 = 8.0;
+    const DEFAULT_BITS_MIN: f32 = 1.0;
+    const DEFAULT_BITS_MAX: f32 = 16.0;
+
+    fn default_bits() -> f32 {
+        Self::DEFAULT_BITS
+    }
+
+    fn default_bits_min() -> f32 {
+        Self::DEFAULT_BITS_MIN
+    }
+
+    fn default_bits_max() -> f32 {
+        Self::DEFAULT_BITS_MAX
+    }
+}
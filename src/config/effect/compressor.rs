@@ -0,0 +1,72 @@
+use crate::config::MidiSlider;
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CompressorConfig {
+    #[serde(default = "CompressorConfig::default_threshold")]
+    pub threshold_db: f32,
+    #[serde(default = "CompressorConfig::default_threshold_min")]
+    pub min_threshold_db: f32,
+    #[serde(default = "CompressorConfig::default_ratio")]
+    pub ratio: f32,
+    #[serde(default = "CompressorConfig::default_ratio_max")]
+    pub max_ratio: f32,
+    #[serde(default = "CompressorConfig::default_knee")]
+    pub knee_db: f32,
+    #[serde(default = "CompressorConfig::default_attack")]
+    pub attack_ms: f32,
+    #[serde(default = "CompressorConfig::default_release")]
+    pub release_ms: f32,
+    #[serde(default)]
+    pub makeup_db: f32,
+    #[serde(default = "CompressorConfig::default_makeup_max")]
+    pub max_makeup_db: f32,
+    /// The name of a `Sidechain` effect whose signal controls the compression.
+    pub sidechain: Option<String>,
+    pub threshold_slider: Option<MidiSlider>,
+    pub ratio_slider: Option<MidiSlider>,
+    pub makeup_slider: Option<MidiSlider>,
+}
+
+impl CompressorConfig {
+    const DEFAULT_THRESHOLD: f32 = -20.0;
+    const DEFAULT_THRESHOLD_MIN: f32 = -60.0;
+    const DEFAULT_RATIO: f32 = 4.0;
+    const DEFAULT_RATIO_MAX: f32 = 20.0;
+    const DEFAULT_KNEE: f32 = 6.0;
+    const DEFAULT_ATTACK: f32 = 10.0;
+    const DEFAULT_RELEASE: f32 = 100.0;
+    const DEFAULT_MAKEUP_MAX: f32 = 24.0;
+
+    fn default_threshold() -> f32 {
+        Self::DEFAULT_THRESHOLD
+    }
+
+    fn default_threshold_min() -> f32 {
+        Self::DEFAULT_THRESHOLD_MIN
+    }
+
+    fn default_ratio() -> f32 {
+        Self::DEFAULT_RATIO
+    }
+
+    fn default_ratio_max() -> f32 {
+        Self::DEFAULT_RATIO_MAX
+    }
+
+    fn default_knee() -> f32 {
+        Self::DEFAULT_KNEE
+    }
+
+    fn default_attack() -> f32 {
+        Self::DEFAULT_ATTACK
+    }
+
+    fn default_release() -> f32 {
+        Self::DEFAULT_RELEASE
+    }
+
+    fn default_makeup_max() -> f32 {
+        Self::DEFAULT_MAKEUP_MAX
+    }
+}
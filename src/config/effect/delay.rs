@@ -1,10 +1,10 @@
 use crate::{
     audio_unit::delay::DelayMs,
-    config::{MidiSlider, NoteOn},
+    config::{MidiSlider, NoteOn, Subdivision},
 };
 use serde::Deserialize;
 
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub struct DelayConfig {
     #[serde(default = "DelayConfig::default_level")]
     pub level: f32,
@@ -16,8 +16,23 @@ pub struct DelayConfig {
     pub max_delay_ms: DelayMs,
     #[serde(default = "DelayConfig::default_num")]
     pub num: u32,
+    /// The pattern of repeats. When unset, there are `num` repeats a beat apart, each `level`
+    /// times quieter than the last.
+    pub taps: Option<Vec<TapConfig>>,
+    /// Bounces each repeat to the opposite side from the last, overriding the taps' `pan`.
+    #[serde(default)]
+    pub ping_pong: bool,
+    pub tone_hz: Option<f32>,
     pub delay_ms_slider: Option<MidiSlider>,
     pub tap_tempo: Option<NoteOn>,
+    /// Sets the beat to the pipeline's shared tempo whenever it changes.
+    #[serde(default)]
+    pub follow_tempo: bool,
+    /// Toggles playing the repeats backwards.
+    pub reverse: Option<NoteOn>,
+    /// When set, the delay emulates a tape echo, and `level` sets how much of each repeat is fed
+    /// back onto the tape.
+    pub tape: Option<TapeConfig>,
 }
 
 impl DelayConfig {
@@ -47,3 +62,115 @@ impl DelayConfig {
         Self::DEFAULT_NUM
     }
 }
+
+/// A single repeat. Its time is set by exactly one of `delay_ms`, `beats` or `subdivision`, where
+/// a beat is the delay's `delay_ms`, and follows the slider and tap tempo.
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct TapConfig {
+    /// A fixed time, which doesn't follow the tempo.
+    pub delay_ms: Option<DelayMs>,
+    pub beats: Option<f32>,
+    pub subdivision: Option<Subdivision>,
+    #[serde(default = "TapConfig::default_level")]
+    pub level: f32,
+    /// The position in the stereo field, from -1 (left) to 1 (right).
+    #[serde(default)]
+    pub pan: f32,
+}
+
+impl TapConfig {
+    const DEFAULT_LEVEL: f32 = 1.0;
+
+    fn default_level() -> f32 {
+        Self::DEFAULT_LEVEL
+    }
+
+    /// The tap's time in beats, if it follows the tempo.
+    pub fn beats(&self) -> Option<f32> {
+        self.beats
+            .or_else(|| self.subdivision.map(Subdivision::beats))
+    }
+
+    /// Whether the time is set exactly once.
+    pub fn has_one_time(&self) -> bool {
+        [
+            self.delay_ms.is_some(),
+            self.beats.is_some(),
+            self.subdivision.is_some(),
+        ]
+        .iter()
+        .filter(|is_set| **is_set)
+        .count()
+            == 1
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TapeConfig {
+    /// The distance to each playback head, as a multiple of `delay_ms`.
+    #[serde(default = "TapeConfig::default_heads")]
+    pub heads: Vec<f32>,
+    /// Sets of heads which can be selected, as indices into `heads`.
+    #[serde(default = "TapeConfig::default_combinations")]
+    pub combinations: Vec<Vec<usize>>,
+    /// The index of the combination which is selected at first.
+    #[serde(default)]
+    pub combination: usize,
+    /// Selects the next combination of heads.
+    pub next_combination: Option<NoteOn>,
+    #[serde(default = "TapeConfig::default_saturation")]
+    pub saturation: f32,
+    #[serde(default = "TapeConfig::default_wow_rate")]
+    pub wow_rate_hz: f32,
+    #[serde(default = "TapeConfig::default_wow_depth")]
+    pub wow_depth_ms: f32,
+    #[serde(default = "TapeConfig::default_flutter_rate")]
+    pub flutter_rate_hz: f32,
+    #[serde(default = "TapeConfig::default_flutter_depth")]
+    pub flutter_depth_ms: f32,
+}
+
+impl TapeConfig {
+    const DEFAULT_HEADS: [f32; 3] = [1.0, 2.0, 3.0];
+    const DEFAULT_SATURATION: f32 = 2.0;
+    const DEFAULT_WOW_RATE: f32 = 0.5;
+    const DEFAULT_WOW_DEPTH: f32 = 1.0;
+    const DEFAULT_FLUTTER_RATE: f32 = 7.0;
+    const DEFAULT_FLUTTER_DEPTH: f32 = 0.1;
+
+    fn default_heads() -> Vec<f32> {
+        Self::DEFAULT_HEADS.to_vec()
+    }
+
+    fn default_combinations() -> Vec<Vec<usize>> {
+        vec![
+            vec![0],
+            vec![1],
+            vec![2],
+            vec![0, 1],
+            vec![1, 2],
+            vec![0, 2],
+            vec![0, 1, 2],
+        ]
+    }
+
+    fn default_saturation() -> f32 {
+        Self::DEFAULT_SATURATION
+    }
+
+    fn default_wow_rate() -> f32 {
+        Self::DEFAULT_WOW_RATE
+    }
+
+    fn default_wow_depth() -> f32 {
+        Self::DEFAULT_WOW_DEPTH
+    }
+
+    fn default_flutter_rate() -> f32 {
+        Self::DEFAULT_FLUTTER_RATE
+    }
+
+    fn default_flutter_depth() -> f32 {
+        Self::DEFAULT_FLUTTER_DEPTH
+    }
+}
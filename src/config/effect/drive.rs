@@ -0,0 +1,89 @@
+use crate::{audio_unit::drive::Shape, config::MidiSlider};
+use serde::Deserialize;
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct DriveConfig {
+    #[serde(default = "DriveConfig::default_shape")]
+    pub shape: Shape,
+    #[serde(default = "DriveConfig::default_drive")]
+    pub drive: f32,
+    #[serde(default = "DriveConfig::default_drive_min")]
+    pub min_drive: f32,
+    #[serde(default = "DriveConfig::default_drive_max")]
+    pub max_drive: f32,
+    #[serde(default = "DriveConfig::default_level")]
+    pub level: f32,
+    #[serde(default = "DriveConfig::default_level_max")]
+    pub max_level: f32,
+    #[serde(default = "DriveConfig::default_pre_filter_hz")]
+    pub pre_filter_hz: f32,
+    #[serde(default = "DriveConfig::default_tone_hz")]
+    pub tone_hz: f32,
+    #[serde(default = "DriveConfig::default_tone_min")]
+    pub min_tone_hz: f32,
+    #[serde(default = "DriveConfig::default_tone_max")]
+    pub max_tone_hz: f32,
+    #[serde(default = "DriveConfig::default_oversampling")]
+    pub oversampling: usize,
+    pub drive_slider: Option<MidiSlider>,
+    pub level_slider: Option<MidiSlider>,
+    pub tone_slider: Option<MidiSlider>,
+}
+
+impl DriveConfig {
+    const DEFAULT_SHAPE: Shape = Shape::SoftClip;
+    const DEFAULT_DRIVE: f32 = 5.0;
+    const DEFAULT_DRIVE_MIN: f32 = 1.0;
+    const DEFAULT_DRIVE_MAX: f32 = 50.0;
+    const DEFAULT_LEVEL: f32 = 0.5;
+    const DEFAULT_LEVEL_MAX: f32 = 1.0;
+    const DEFAULT_PRE_FILTER_HZ: f32 = 100.0;
+    const DEFAULT_TONE_HZ: f32 = 4_000.0;
+    const DEFAULT_TONE_MIN: f32 = 500.0;
+    const DEFAULT_TONE_MAX: f32 = 10_000.0;
+    const DEFAULT_OVERSAMPLING: usize = 4;
+
+    fn default_shape() -> Shape {
+        Self::DEFAULT_SHAPE
+    }
+
+    fn default_drive() -> f32 {
+        Self::DEFAULT_DRIVE
+    }
+
+    fn default_drive_min() -> f32 {
+        Self::DEFAULT_DRIVE_MIN
+    }
+
+    fn default_drive_max() -> f32 {
+        Self::DEFAULT_DRIVE_MAX
+    }
+
+    fn default_level() -> f32 {
+        Self::DEFAULT_LEVEL
+    }
+
+    fn default_level_max() -> f32 {
+        Self::DEFAULT_LEVEL_MAX
+    }
+
+    fn default_pre_filter_hz() -> f32 {
+        Self::DEFAULT_PRE_FILTER_HZ
+    }
+
+    fn default_tone_hz() -> f32 {
+        Self::DEFAULT_TONE_HZ
+    }
+
+    fn default_tone_min() -> f32 {
+        Self::DEFAULT_TONE_MIN
+    }
+
+    fn default_tone_max() -> f32 {
+        Self::DEFAULT_TONE_MAX
+    }
+
+    fn default_oversampling() -> usize {
+        Self::DEFAULT_OVERSAMPLING
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{audio_unit::biquad::Kind, config::MidiSlider};
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct EqConfig {
+    pub bands: Vec<EqBandConfig>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct EqBandConfig {
+    #[serde(default = "EqBandConfig::default_kind")]
+    pub kind: Kind,
+    pub frequency: f32,
+    #[serde(default = "EqBandConfig::default_frequency_min")]
+    pub min_frequency: f32,
+    #[serde(default = "EqBandConfig::default_frequency_max")]
+    pub max_frequency: f32,
+    #[serde(default)]
+    pub gain_db: f32,
+    #[serde(default = "EqBandConfig::default_gain_min")]
+    pub min_gain_db: f32,
+    #[serde(default = "EqBandConfig::default_gain_max")]
+    pub max_gain_db: f32,
+    #[serde(default = "EqBandConfig::default_q")]
+    pub q: f32,
+    #[serde(default = "EqBandConfig::default_q_min")]
+    pub min_q: f32,
+    #[serde(default = "EqBandConfig::default_q_max")]
+    pub max_q: f32,
+    pub frequency_slider: Option<MidiSlider>,
+    pub gain_slider: Option<MidiSlider>,
+    pub q_slider: Option<MidiSlider>,
+}
+
+impl EqBandConfig {
+    const DEFAULT_KIND: Kind = Kind::Peak;
+    const DEFAULT_FREQUENCY_MIN: f32 = 20.0;
+    const DEFAULT_FREQUENCY_MAX: f32 = 20_000.0;
+    const DEFAULT_GAIN_MIN: f32 = -15.0;
+    const DEFAULT_GAIN_MAX: f32 = 15.0;
+    const DEFAULT_Q: f32 = 0.707;
+    const DEFAULT_Q_MIN: f32 = 0.1;
+    const DEFAULT_Q_MAX: f32 = 10.0;
+
+    fn default_kind() -> Kind {
+        Self::DEFAULT_KIND
+    }
+
+    fn default_frequency_min() -> f32 {
+        Self::DEFAULT_FREQUENCY_MIN
+    }
+
+    fn default_frequency_max() -> f32 {
+        Self::DEFAULT_FREQUENCY_MAX
+    }
+
+    fn default_gain_min() -> f32 {
+        Self::DEFAULT_GAIN_MIN
+    }
+
+    fn default_gain_max() -> f32 {
+        Self::DEFAULT_GAIN_MAX
+    }
+
+    fn default_q() -> f32 {
+        Self::DEFAULT_Q
+    }
+
+    fn default_q_min() -> f32 {
+        Self::DEFAULT_Q_MIN
+    }
+
+    fn default_q_max() -> f32 {
+        Self::DEFAULT_Q_MAX
+    }
+}
@@ -0,0 +1,41 @@
+use crate::config::NoteOn;
+use serde::Deserialize;
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct FreezeConfig {
+    pub freeze: NoteOn,
+    /// When true, pressing `freeze` a second time releases the sound. When false, the sound is
+    /// released when the note is let go.
+    #[serde(default = "FreezeConfig::default_latch")]
+    pub latch: bool,
+    #[serde(default = "FreezeConfig::default_level")]
+    pub level: f32,
+    #[serde(default = "FreezeConfig::default_release")]
+    pub release_ms: u32,
+    /// The FFT size. Larger frames give a smoother drone, but take longer to capture.
+    #[serde(default = "FreezeConfig::default_frame_size")]
+    pub frame_size: usize,
+}
+
+impl FreezeConfig {
+    const DEFAULT_LATCH: bool = true;
+    const DEFAULT_LEVEL: f32 = 1.0;
+    const DEFAULT_RELEASE: u32 = 500;
+    const DEFAULT_FRAME_SIZE: usize = 4096;
+
+    fn default_latch() -> bool {
+        Self::DEFAULT_LATCH
+    }
+
+    fn default_level() -> f32 {
+        Self::DEFAULT_LEVEL
+    }
+
+    fn default_release() -> u32 {
+        Self::DEFAULT_RELEASE
+    }
+
+    fn default_frame_size() -> usize {
+        Self::DEFAULT_FRAME_SIZE
+    }
+}
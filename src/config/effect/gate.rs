@@ -0,0 +1,64 @@
+use crate::config::MidiSlider;
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct GateConfig {
+    /// The level at which the gate opens.
+    #[serde(default = "GateConfig::default_threshold")]
+    pub threshold_db: f32,
+    #[serde(default = "GateConfig::default_threshold_min")]
+    pub min_threshold_db: f32,
+    /// How far below the threshold the level needs to fall before the gate closes.
+    #[serde(default = "GateConfig::default_hysteresis")]
+    pub hysteresis_db: f32,
+    #[serde(default = "GateConfig::default_hold")]
+    pub hold_ms: f32,
+    #[serde(default = "GateConfig::default_attack")]
+    pub attack_ms: f32,
+    #[serde(default = "GateConfig::default_release")]
+    pub release_ms: f32,
+    /// The attenuation applied while the gate is closed.
+    #[serde(default = "GateConfig::default_range")]
+    pub range_db: f32,
+    /// The name of a `Sidechain` effect whose signal opens and closes the gate.
+    pub sidechain: Option<String>,
+    pub threshold_slider: Option<MidiSlider>,
+}
+
+impl GateConfig {
+    const DEFAULT_THRESHOLD: f32 = -50.0;
+    const DEFAULT_THRESHOLD_MIN: f32 = -90.0;
+    const DEFAULT_HYSTERESIS: f32 = 6.0;
+    const DEFAULT_HOLD: f32 = 50.0;
+    const DEFAULT_ATTACK: f32 = 1.0;
+    const DEFAULT_RELEASE: f32 = 100.0;
+    const DEFAULT_RANGE: f32 = -80.0;
+
+    fn default_threshold() -> f32 {
+        Self::DEFAULT_THRESHOLD
+    }
+
+    fn default_threshold_min() -> f32 {
+        Self::DEFAULT_THRESHOLD_MIN
+    }
+
+    fn default_hysteresis() -> f32 {
+        Self::DEFAULT_HYSTERESIS
+    }
+
+    fn default_hold() -> f32 {
+        Self::DEFAULT_HOLD
+    }
+
+    fn default_attack() -> f32 {
+        Self::DEFAULT_ATTACK
+    }
+
+    fn default_release() -> f32 {
+        Self::DEFAULT_RELEASE
+    }
+
+    fn default_range() -> f32 {
+        Self::DEFAULT_RANGE
+    }
+}
@@ -0,0 +1,93 @@
+use crate::config::MidiSlider;
+use serde::Deserialize;
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct GranularConfig {
+    /// The number of grains started per second.
+    #[serde(default = "GranularConfig::default_density")]
+    pub density: f32,
+    #[serde(default = "GranularConfig::default_density_max")]
+    pub max_density: f32,
+    /// The length of each grain.
+    #[serde(default = "GranularConfig::default_size")]
+    pub size_ms: f32,
+    #[serde(default = "GranularConfig::default_size_min")]
+    pub min_size_ms: f32,
+    #[serde(default = "GranularConfig::default_size_max")]
+    pub max_size_ms: f32,
+    /// How far back in the recording grains can start.
+    #[serde(default = "GranularConfig::default_position")]
+    pub position_ms: f32,
+    #[serde(default = "GranularConfig::default_position_max")]
+    pub max_position_ms: f32,
+    /// The pitch of the grains, in semitones.
+    #[serde(default)]
+    pub pitch: f32,
+    /// How far each grain's pitch can stray from `pitch` at random, in semitones.
+    #[serde(default)]
+    pub pitch_spread: f32,
+    /// How far grains can be panned from the center at random, from 0 to 1.
+    #[serde(default = "GranularConfig::default_stereo_spread")]
+    pub stereo_spread: f32,
+    #[serde(default)]
+    pub feedback: f32,
+    #[serde(default = "GranularConfig::default_mix")]
+    pub mix: f32,
+    pub density_slider: Option<MidiSlider>,
+    pub size_slider: Option<MidiSlider>,
+    pub position_slider: Option<MidiSlider>,
+    /// Sets the pitch between -12 and 12 semitones.
+    pub pitch_slider: Option<MidiSlider>,
+    /// Sets the pitch spread between 0 and 12 semitones.
+    pub pitch_spread_slider: Option<MidiSlider>,
+    pub feedback_slider: Option<MidiSlider>,
+    pub mix_slider: Option<MidiSlider>,
+}
+
+impl GranularConfig {
+    const DEFAULT_DENSITY: f32 = 20.0;
+    const DEFAULT_DENSITY_MAX: f32 = 100.0;
+    const DEFAULT_SIZE: f32 = 100.0;
+    const DEFAULT_SIZE_MIN: f32 = 10.0;
+    const DEFAULT_SIZE_MAX: f32 = 500.0;
+    const DEFAULT_POSITION: f32 = 500.0;
+    const DEFAULT_POSITION_MAX: f32 = 4_000.0;
+    const DEFAULT_STEREO_SPREAD: f32 = 0.5;
+    const DEFAULT_MIX: f32 = 0.5;
+
+    fn default_density() -> f32 {
+        Self::DEFAULT_DENSITY
+    }
+
+    fn default_density_max() -> f32 {
+        Self::DEFAULT_DENSITY_MAX
+    }
+
+    fn default_size() -> f32 {
+        Self::DEFAULT_SIZE
+    }
+
+    fn default_size_min() -> f32 {
+        Self::DEFAULT_SIZE_MIN
+    }
+
+    fn default_size_max() -> f32 {
+        Self::DEFAULT_SIZE_MAX
+    }
+
+    fn default_position() -> f32 {
+        Self::DEFAULT_POSITION
+    }
+
+    fn default_position_max() -> f32 {
+        Self::DEFAULT_POSITION_MAX
+    }
+
+    fn default_stereo_spread() -> f32 {
+        Self::DEFAULT_STEREO_SPREAD
+    }
+
+    fn default_mix() -> f32 {
+        Self::DEFAULT_MIX
+    }
+}
@@ -0,0 +1,38 @@
+use crate::config::MidiSlider;
+use serde::Deserialize;
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct LimiterConfig {
+    #[serde(default = "LimiterConfig::default_ceiling")]
+    pub ceiling_db: f32,
+    #[serde(default = "LimiterConfig::default_ceiling_min")]
+    pub min_ceiling_db: f32,
+    #[serde(default = "LimiterConfig::default_lookahead")]
+    pub lookahead_ms: f32,
+    #[serde(default = "LimiterConfig::default_release")]
+    pub release_ms: f32,
+    pub ceiling_slider: Option<MidiSlider>,
+}
+
+impl LimiterConfig {
+    const DEFAULT_CEILING: f32 = -0.3;
+    const DEFAULT_CEILING_MIN: f32 = -20.0;
+    const DEFAULT_LOOKAHEAD: f32 = 5.0;
+    const DEFAULT_RELEASE: f32 = 50.0;
+
+    fn default_ceiling() -> f32 {
+        Self::DEFAULT_CEILING
+    }
+
+    fn default_ceiling_min() -> f32 {
+        Self::DEFAULT_CEILING_MIN
+    }
+
+    fn default_lookahead() -> f32 {
+        Self::DEFAULT_LOOKAHEAD
+    }
+
+    fn default_release() -> f32 {
+        Self::DEFAULT_RELEASE
+    }
+}
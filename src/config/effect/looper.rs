@@ -1,18 +1,157 @@
-use crate::config::NoteOn;
+use crate::{
+    audio_unit::looper::Quantize,
+    config::{MidiOutput, MidiSlider, NoteOn},
+};
 use serde::Deserialize;
 
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub struct LooperConfig {
     #[serde(default = "LooperConfig::default_loop_max")]
     pub max_ms: u32,
-    pub toggle: NoteOn,
-    pub overdub: NoteOn,
+    /// The notes for a single track. Ignored when `tracks` is set.
+    pub toggle: Option<NoteOn>,
+    pub overdub: Option<NoteOn>,
+    pub undo: Option<NoteOn>,
+    pub redo: Option<NoteOn>,
+    #[serde(flatten)]
+    pub commands: LooperCommandsConfig,
+    /// A WAV file to load into the track at startup, so that it starts out playing.
+    pub file: Option<String>,
+    /// Saves every track, and the overdub layers which can be undone, to WAV files.
+    pub save: Option<NoteOn>,
+    /// The directory which loops are saved to.
+    #[serde(default = "LooperConfig::default_save_directory")]
+    pub save_directory: String,
+    /// How much audio is kept so that overdubs can be undone, shared between the tracks. The
+    /// oldest overdubs are forgotten first.
+    #[serde(default = "LooperConfig::default_max_history_ms")]
+    pub max_history_ms: u32,
+    /// Toggles playing the loop backwards.
+    pub reverse: Option<NoteOn>,
+    /// Waits for the next beat or bar of the shared tempo to start and stop recording.
+    #[serde(default)]
+    pub quantize: Quantize,
+    #[serde(default = "LooperConfig::default_beats_per_bar")]
+    pub beats_per_bar: u32,
+    /// When set, the first loop sets the shared tempo, as this many beats.
+    pub loop_beats: Option<u32>,
+    /// How long the end of each recording or overdub pass is crossfaded into its start, so that
+    /// the loop doesn't click as it wraps around.
+    #[serde(default = "LooperConfig::default_crossfade_ms")]
+    pub crossfade_ms: f32,
+    /// How long tracks take to fade in and out as they start, stop, and are muted.
+    #[serde(default = "LooperConfig::default_fade_ms")]
+    pub fade_ms: f32,
+    /// How much of the existing loop is kept on each overdub pass, so that old layers fade away.
+    #[serde(default = "LooperConfig::default_feedback")]
+    pub feedback: f32,
+    pub feedback_slider: Option<MidiSlider>,
+    /// Lights each track's toggle note on a controller: full velocity while recording or
+    /// overdubbing, half while playing, and off otherwise.
+    pub midi_output: Option<MidiOutput>,
+    /// Several synchronized tracks. The first loop recorded sets the length of the others, which
+    /// are rounded up to a multiple of it.
+    #[serde(default)]
+    pub tracks: Vec<LooperTrackConfig>,
 }
 
 impl LooperConfig {
     const DEFAULT_LOOPER_MAX: u32 = 60_000;
+    const DEFAULT_MAX_HISTORY_MS: u32 = 120_000;
+    const DEFAULT_SAVE_DIRECTORY: &'static str = "loops";
+    const DEFAULT_BEATS_PER_BAR: u32 = 4;
+    const DEFAULT_CROSSFADE_MS: f32 = 10.0;
+    const DEFAULT_FADE_MS: f32 = 10.0;
+    const DEFAULT_FEEDBACK: f32 = 1.0;
 
     fn default_loop_max() -> u32 {
         Self::DEFAULT_LOOPER_MAX
     }
+
+    fn default_max_history_ms() -> u32 {
+        Self::DEFAULT_MAX_HISTORY_MS
+    }
+
+    fn default_beats_per_bar() -> u32 {
+        Self::DEFAULT_BEATS_PER_BAR
+    }
+
+    fn default_crossfade_ms() -> f32 {
+        Self::DEFAULT_CROSSFADE_MS
+    }
+
+    fn default_fade_ms() -> f32 {
+        Self::DEFAULT_FADE_MS
+    }
+
+    fn default_feedback() -> f32 {
+        Self::DEFAULT_FEEDBACK
+    }
+
+    fn default_save_directory() -> String {
+        Self::DEFAULT_SAVE_DIRECTORY.to_string()
+    }
+
+    /// The configured tracks, or a single track using the top level notes.
+    pub fn tracks(&self) -> Vec<LooperTrackConfig> {
+        if !self.tracks.is_empty() {
+            return self.tracks.clone();
+        }
+
+        self.toggle
+            .map(|toggle| LooperTrackConfig {
+                toggle,
+                overdub: self.overdub,
+                mute: None,
+                undo: self.undo,
+                redo: self.redo,
+                commands: self.commands,
+                file: self.file.clone(),
+                level: LooperTrackConfig::DEFAULT_LEVEL,
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LooperTrackConfig {
+    /// Cycles the track from off, to recording, to playing, and back to off.
+    pub toggle: NoteOn,
+    pub overdub: Option<NoteOn>,
+    pub mute: Option<NoteOn>,
+    /// Undoes the track's last overdub pass.
+    pub undo: Option<NoteOn>,
+    /// Redoes the track's last undone overdub pass.
+    pub redo: Option<NoteOn>,
+    #[serde(flatten)]
+    pub commands: LooperCommandsConfig,
+    /// A WAV file to load into the track at startup, so that it starts out playing.
+    pub file: Option<String>,
+    #[serde(default = "LooperTrackConfig::default_level")]
+    pub level: f32,
+}
+
+impl LooperTrackConfig {
+    const DEFAULT_LEVEL: f32 = 1.0;
+
+    fn default_level() -> f32 {
+        Self::DEFAULT_LEVEL
+    }
+}
+
+/// Notes for a track's other commands, which are all optional.
+#[derive(Copy, Clone, Debug, Default, Deserialize)]
+pub struct LooperCommandsConfig {
+    /// Silences the track, while keeping its loop.
+    pub stop: Option<NoteOn>,
+    /// Plays a stopped track, in time with the others, or from the top if nothing else is playing.
+    pub play: Option<NoteOn>,
+    /// Plays the track through once, from the top.
+    pub one_shot: Option<NoteOn>,
+    pub clear: Option<NoteOn>,
+    /// Doubles the length of the loop by repeating it.
+    pub multiply: Option<NoteOn>,
+    pub half_speed: Option<NoteOn>,
+    pub double_speed: Option<NoteOn>,
 }
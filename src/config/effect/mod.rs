@@ -1,16 +1,80 @@
+mod bitcrusher;
+mod compressor;
 mod delay;
+mod drive;
+mod eq;
+mod freeze;
+mod gate;
+mod granular;
+mod limiter;
 mod looper;
+mod modulation;
+mod phaser;
+mod pitch_shift;
+mod ring_modulator;
+mod sample_rate_reducer;
+mod sidechain;
+mod tremolo;
+mod tuner;
+mod wah;
 
-pub use delay::DelayConfig;
-pub use looper::LooperConfig;
+pub use bitcrusher::BitcrusherConfig;
+pub use compressor::CompressorConfig;
+pub use delay::{DelayConfig, TapConfig, TapeConfig};
+pub use drive::DriveConfig;
+pub use eq::{EqBandConfig, EqConfig};
+pub use freeze::FreezeConfig;
+pub use gate::GateConfig;
+pub use granular::GranularConfig;
+pub use limiter::LimiterConfig;
+pub use looper::{LooperCommandsConfig, LooperConfig, LooperTrackConfig};
+pub use modulation::{ModulationConfig, ModulationKind};
+pub use phaser::PhaserConfig;
+pub use pitch_shift::{PitchShiftConfig, PitchShiftVoiceConfig};
+pub use ring_modulator::RingModulatorConfig;
+pub use sample_rate_reducer::SampleRateReducerConfig;
+pub use sidechain::SidechainConfig;
+pub use tremolo::TremoloConfig;
+pub use tuner::TunerConfig;
+pub use wah::WahConfig;
 
 use serde::Deserialize;
 
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(tag = "type")]
 pub enum Effect {
     Transparent,
     Delay(DelayConfig),
+    Drive(DriveConfig),
+    Eq(EqConfig),
     Looper(LooperConfig),
+    Compressor(CompressorConfig),
+    Limiter(LimiterConfig),
+    Gate(GateConfig),
+    Sidechain(SidechainConfig),
+    Chorus(ModulationConfig),
+    Flanger(ModulationConfig),
+    Vibrato(ModulationConfig),
+    Tremolo(TremoloConfig),
+    HarmonicTremolo(TremoloConfig),
+    AutoPan(TremoloConfig),
+    Phaser(PhaserConfig),
+    PitchShift(PitchShiftConfig),
+    Freeze(FreezeConfig),
+    Wah(WahConfig),
+    Bitcrusher(BitcrusherConfig),
+    SampleRateReducer(SampleRateReducerConfig),
+    RingModulator(RingModulatorConfig),
+    Tuner(TunerConfig),
+    Granular(GranularConfig),
     Fft,
 }
+
+impl Effect {
+    /// The effect's type, as it is written in the config.
+    pub fn name(&self) -> String {
+        let mut name = format!("{:?}", self);
+        name.truncate(name.find('(').unwrap_or(name.len()));
+        name
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{
+    audio_unit::{lfo::Shape, modulated_delay},
+    config::{MidiSlider, NoteOn, Subdivision},
+};
+use serde::Deserialize;
+
+/// Which of the effects a `ModulationConfig` is for, which decides the defaults of its fields.
+#[derive(Copy, Clone, Debug)]
+pub enum ModulationKind {
+    Chorus,
+    Flanger,
+    Vibrato,
+}
+
+impl ModulationKind {
+    /// The values used for the fields of a `ModulationConfig` which are omitted:
+    ///
+    /// | kind    | shape    | rate_hz | delay_ms | depth_ms | feedback | mix |
+    /// |---------|----------|---------|----------|----------|----------|-----|
+    /// | Chorus  | Sine     | 0.8     | 15       | 5        | 0        | 0.5 |
+    /// | Flanger | Triangle | 0.25    | 1        | 3        | 0.5      | 0.5 |
+    /// | Vibrato | Sine     | 5       | 2        | 3        | 0        | 1   |
+    ///
+    /// The chorus also offsets the right channel's LFO by a quarter of a cycle, which can't be
+    /// configured.
+    pub fn defaults(self) -> modulated_delay::Parameters {
+        match self {
+            ModulationKind::Chorus => modulated_delay::Parameters {
+                shape: Shape::Sine,
+                rate_hz: 0.8,
+                delay_ms: 15.0,
+                depth_ms: 5.0,
+                feedback: 0.0,
+                mix: 0.5,
+                stereo_phase: 0.25,
+            },
+            ModulationKind::Flanger => modulated_delay::Parameters {
+                shape: Shape::Triangle,
+                rate_hz: 0.25,
+                delay_ms: 1.0,
+                depth_ms: 3.0,
+                feedback: 0.5,
+                mix: 0.5,
+                stereo_phase: 0.0,
+            },
+            ModulationKind::Vibrato => modulated_delay::Parameters {
+                shape: Shape::Sine,
+                rate_hz: 5.0,
+                delay_ms: 2.0,
+                depth_ms: 3.0,
+                feedback: 0.0,
+                mix: 1.0,
+                stereo_phase: 0.0,
+            },
+        }
+    }
+}
+
+/// Configuration shared by chorus, flanger and vibrato.
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct ModulationConfig {
+    // These default to values which suit the type of effect, so can't use serde's defaults. See
+    // `ModulationKind::defaults` for the values, and `parameters` for the resolved settings.
+    pub shape: Option<Shape>,
+    pub rate_hz: Option<f32>,
+    pub delay_ms: Option<f32>,
+    pub depth_ms: Option<f32>,
+    pub feedback: Option<f32>,
+    pub mix: Option<f32>,
+    #[serde(default = "ModulationConfig::default_rate_min")]
+    pub min_rate_hz: f32,
+    #[serde(default = "ModulationConfig::default_rate_max")]
+    pub max_rate_hz: f32,
+    pub rate_slider: Option<MidiSlider>,
+    /// Sweeps the depth between 0 and twice `depth_ms`.
+    pub depth_slider: Option<MidiSlider>,
+    pub feedback_slider: Option<MidiSlider>,
+    /// Locks the rate to the tapped tempo, and restarts the LFO on each tap.
+    pub tap_tempo: Option<NoteOn>,
+    /// The length of one LFO cycle when following the tapped tempo.
+    #[serde(default)]
+    pub subdivision: Subdivision,
+}
+
+impl ModulationConfig {
+    const DEFAULT_RATE_MIN: f32 = 0.05;
+    const DEFAULT_RATE_MAX: f32 = 10.0;
+
+    /// The settings for `kind`, with the omitted fields filled in from `ModulationKind::defaults`.
+    pub fn parameters(&self, kind: ModulationKind) -> modulated_delay::Parameters {
+        let defaults = kind.defaults();
+
+        modulated_delay::Parameters {
+            shape: self.shape.unwrap_or(defaults.shape),
+            rate_hz: self.rate_hz.unwrap_or(defaults.rate_hz),
+            delay_ms: self.delay_ms.unwrap_or(defaults.delay_ms),
+            depth_ms: self.depth_ms.unwrap_or(defaults.depth_ms),
+            feedback: self.feedback.unwrap_or(defaults.feedback),
+            mix: self.mix.unwrap_or(defaults.mix),
+            stereo_phase: defaults.stereo_phase,
+        }
+    }
+
+    fn default_rate_min() -> f32 {
+        Self::DEFAULT_RATE_MIN
+    }
+
+    fn default_rate_max() -> f32 {
+        Self::DEFAULT_RATE_MAX
+    }
+}
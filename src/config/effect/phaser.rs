@@ -0,0 +1,107 @@
+use crate::{
+    audio_unit::lfo::Shape,
+    config::{MidiSlider, NoteOn, Subdivision},
+};
+use serde::Deserialize;
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct PhaserConfig {
+    /// The number of all-pass stages: 4, 6, 8 or 12.
+    #[serde(default = "PhaserConfig::default_stages")]
+    pub stages: usize,
+    #[serde(default = "PhaserConfig::default_shape")]
+    pub shape: Shape,
+    #[serde(default = "PhaserConfig::default_rate")]
+    pub rate_hz: f32,
+    #[serde(default = "PhaserConfig::default_rate_min")]
+    pub min_rate_hz: f32,
+    #[serde(default = "PhaserConfig::default_rate_max")]
+    pub max_rate_hz: f32,
+    /// How far the sweep moves either side of the center frequency, in octaves.
+    #[serde(default = "PhaserConfig::default_depth")]
+    pub depth: f32,
+    #[serde(default = "PhaserConfig::default_depth_max")]
+    pub max_depth: f32,
+    #[serde(default = "PhaserConfig::default_feedback")]
+    pub feedback: f32,
+    #[serde(default = "PhaserConfig::default_center")]
+    pub center_hz: f32,
+    #[serde(default = "PhaserConfig::default_center_min")]
+    pub min_center_hz: f32,
+    #[serde(default = "PhaserConfig::default_center_max")]
+    pub max_center_hz: f32,
+    #[serde(default = "PhaserConfig::default_mix")]
+    pub mix: f32,
+    pub rate_slider: Option<MidiSlider>,
+    pub depth_slider: Option<MidiSlider>,
+    pub feedback_slider: Option<MidiSlider>,
+    pub center_slider: Option<MidiSlider>,
+    /// Locks the rate to the tapped tempo, and restarts the LFO on each tap.
+    pub tap_tempo: Option<NoteOn>,
+    /// The length of one LFO cycle when following the tapped tempo.
+    #[serde(default)]
+    pub subdivision: Subdivision,
+}
+
+impl PhaserConfig {
+    const DEFAULT_STAGES: usize = 4;
+    const DEFAULT_SHAPE: Shape = Shape::Sine;
+    const DEFAULT_RATE: f32 = 0.5;
+    const DEFAULT_RATE_MIN: f32 = 0.05;
+    const DEFAULT_RATE_MAX: f32 = 10.0;
+    const DEFAULT_DEPTH: f32 = 1.5;
+    const DEFAULT_DEPTH_MAX: f32 = 3.0;
+    const DEFAULT_FEEDBACK: f32 = 0.5;
+    const DEFAULT_CENTER: f32 = 800.0;
+    const DEFAULT_CENTER_MIN: f32 = 100.0;
+    const DEFAULT_CENTER_MAX: f32 = 4_000.0;
+    const DEFAULT_MIX: f32 = 0.5;
+
+    fn default_stages() -> usize {
+        Self::DEFAULT_STAGES
+    }
+
+    fn default_shape() -> Shape {
+        Self::DEFAULT_SHAPE
+    }
+
+    fn default_rate() -> f32 {
+        Self::DEFAULT_RATE
+    }
+
+    fn default_rate_min() -> f32 {
+        Self::DEFAULT_RATE_MIN
+    }
+
+    fn default_rate_max() -> f32 {
+        Self::DEFAULT_RATE_MAX
+    }
+
+    fn default_depth() -> f32 {
+        Self::DEFAULT_DEPTH
+    }
+
+    fn default_depth_max() -> f32 {
+        Self::DEFAULT_DEPTH_MAX
+    }
+
+    fn default_feedback() -> f32 {
+        Self::DEFAULT_FEEDBACK
+    }
+
+    fn default_center() -> f32 {
+        Self::DEFAULT_CENTER
+    }
+
+    fn default_center_min() -> f32 {
+        Self::DEFAULT_CENTER_MIN
+    }
+
+    fn default_center_max() -> f32 {
+        Self::DEFAULT_CENTER_MAX
+    }
+
+    fn default_mix() -> f32 {
+        Self::DEFAULT_MIX
+    }
+}
@@ -0,0 +1,57 @@
+use crate::config::NoteOn;
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PitchShiftConfig {
+    pub voices: Vec<PitchShiftVoiceConfig>,
+    /// The balance between the dry signal and the shifted voices.
+    #[serde(default = "PitchShiftConfig::default_mix")]
+    pub mix: f32,
+    /// The length of the grains. Longer windows smear transients, shorter ones sound grainier.
+    #[serde(default = "PitchShiftConfig::default_window")]
+    pub window_ms: f32,
+}
+
+impl PitchShiftConfig {
+    const DEFAULT_MIX: f32 = 0.5;
+    const DEFAULT_WINDOW: f32 = 50.0;
+
+    fn default_mix() -> f32 {
+        Self::DEFAULT_MIX
+    }
+
+    fn default_window() -> f32 {
+        Self::DEFAULT_WINDOW
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct PitchShiftVoiceConfig {
+    pub semitones: f32,
+    #[serde(default)]
+    pub cents: f32,
+    #[serde(default = "PitchShiftVoiceConfig::default_level")]
+    pub level: f32,
+    /// Whether the voice is playing before it is toggled.
+    #[serde(default = "PitchShiftVoiceConfig::default_enabled")]
+    pub enabled: bool,
+    pub toggle: Option<NoteOn>,
+}
+
+impl PitchShiftVoiceConfig {
+    const DEFAULT_LEVEL: f32 = 1.0;
+    const DEFAULT_ENABLED: bool = true;
+
+    fn default_level() -> f32 {
+        Self::DEFAULT_LEVEL
+    }
+
+    fn default_enabled() -> bool {
+        Self::DEFAULT_ENABLED
+    }
+
+    /// The interval of the voice, in semitones.
+    pub fn interval(&self) -> f32 {
+        self.semitones + self.cents / 100.0
+    }
+}
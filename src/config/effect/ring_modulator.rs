@@ -0,0 +1,52 @@
+use crate::{
+    audio_unit::lfo::Shape,
+    config::{MidiNotes, MidiSlider},
+};
+use serde::Deserialize;
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct RingModulatorConfig {
+    /// The shape of the carrier. `Sine` is smooth and bell-like, `Square` is harsher.
+    #[serde(default = "RingModulatorConfig::default_shape")]
+    pub shape: Shape,
+    #[serde(default = "RingModulatorConfig::default_frequency")]
+    pub frequency_hz: f32,
+    #[serde(default = "RingModulatorConfig::default_frequency_min")]
+    pub min_frequency_hz: f32,
+    #[serde(default = "RingModulatorConfig::default_frequency_max")]
+    pub max_frequency_hz: f32,
+    #[serde(default = "RingModulatorConfig::default_mix")]
+    pub mix: f32,
+    pub frequency_slider: Option<MidiSlider>,
+    pub mix_slider: Option<MidiSlider>,
+    /// When set, each note played on this channel tunes the carrier to its pitch.
+    pub notes: Option<MidiNotes>,
+}
+
+impl RingModulatorConfig {
+    const DEFAULT_SHAPE: Shape = Shape::Sine;
+    const DEFAULT_FREQUENCY: f32 = 440.0;
+    const DEFAULT_FREQUENCY_MIN: f32 = 20.0;
+    const DEFAULT_FREQUENCY_MAX: f32 = 2_000.0;
+    const DEFAULT_MIX: f32 = 1.0;
+
+    fn default_shape() -> Shape {
+        Self::DEFAULT_SHAPE
+    }
+
+    fn default_frequency() -> f32 {
+        Self::DEFAULT_FREQUENCY
+    }
+
+    fn default_frequency_min() -> f32 {
+        Self::DEFAULT_FREQUENCY_MIN
+    }
+
+    fn default_frequency_max() -> f32 {
+        Self::DEFAULT_FREQUENCY_MAX
+    }
+
+    fn default_mix() -> f32 {
+        Self::DEFAULT_MIX
+    }
+}
@@ -0,0 +1,31 @@
+use crate::config::MidiSlider;
+use serde::Deserialize;
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct SampleRateReducerConfig {
+    #[serde(default = "SampleRateReducerConfig::default_rate")]
+    pub rate_hz: f32,
+    #[serde(default = "SampleRateReducerConfig::default_rate_min")]
+    pub min_rate_hz: f32,
+    #[serde(default = "SampleRateReducerConfig::default_rate_max")]
+    pub max_rate_hz: f32,
+    pub rate_slider: Option<MidiSlider>,
+}
+
+impl SampleRateReducerConfig {
+    const DEFAULT_RATE: f32 = 8_000.0;
+    const DEFAULT_RATE_MIN: f32 = 500.0;
+    const DEFAULT_RATE_MAX: f32 = 22_050.0;
+
+    fn default_rate() -> f32 {
+        Self::DEFAULT_RATE
+    }
+
+    fn default_rate_min() -> f32 {
+        Self::DEFAULT_RATE_MIN
+    }
+
+    fn default_rate_max() -> f32 {
+        Self::DEFAULT_RATE_MAX
+    }
+}
@@ -0,0 +1,6 @@
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SidechainConfig {
+    pub name: String,
+}
@@ -0,0 +1,63 @@
+use crate::{
+    audio_unit::lfo::Shape,
+    config::{MidiSlider, NoteOn, Subdivision},
+};
+use serde::Deserialize;
+
+/// Configuration shared by tremolo, harmonic tremolo and auto-pan.
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct TremoloConfig {
+    #[serde(default = "TremoloConfig::default_shape")]
+    pub shape: Shape,
+    #[serde(default = "TremoloConfig::default_rate")]
+    pub rate_hz: f32,
+    #[serde(default = "TremoloConfig::default_rate_min")]
+    pub min_rate_hz: f32,
+    #[serde(default = "TremoloConfig::default_rate_max")]
+    pub max_rate_hz: f32,
+    #[serde(default = "TremoloConfig::default_depth")]
+    pub depth: f32,
+    /// The frequency which separates the bands in harmonic tremolo.
+    #[serde(default = "TremoloConfig::default_crossover")]
+    pub crossover_hz: f32,
+    pub rate_slider: Option<MidiSlider>,
+    pub depth_slider: Option<MidiSlider>,
+    /// Locks the rate to the tapped tempo, and restarts the LFO on each tap.
+    pub tap_tempo: Option<NoteOn>,
+    /// The length of one LFO cycle when following the tapped tempo.
+    #[serde(default)]
+    pub subdivision: Subdivision,
+}
+
+impl TremoloConfig {
+    const DEFAULT_SHAPE: Shape = Shape::Sine;
+    const DEFAULT_RATE: f32 = 4.0;
+    const DEFAULT_RATE_MIN: f32 = 0.1;
+    const DEFAULT_RATE_MAX: f32 = 15.0;
+    const DEFAULT_DEPTH: f32 = 0.5;
+    const DEFAULT_CROSSOVER: f32 = 800.0;
+
+    fn default_shape() -> Shape {
+        Self::DEFAULT_SHAPE
+    }
+
+    fn default_rate() -> f32 {
+        Self::DEFAULT_RATE
+    }
+
+    fn default_rate_min() -> f32 {
+        Self::DEFAULT_RATE_MIN
+    }
+
+    fn default_rate_max() -> f32 {
+        Self::DEFAULT_RATE_MAX
+    }
+
+    fn default_depth() -> f32 {
+        Self::DEFAULT_DEPTH
+    }
+
+    fn default_crossover() -> f32 {
+        Self::DEFAULT_CROSSOVER
+    }
+}
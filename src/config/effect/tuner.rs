@@ -0,0 +1,39 @@
+use crate::config::{MidiOutput, NoteOn};
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TunerConfig {
+    /// Turns the tuner on and off.
+    pub toggle: NoteOn,
+    /// When true, the output is silenced while the tuner is on.
+    #[serde(default = "TunerConfig::default_mute")]
+    pub mute: bool,
+    /// The frequency of A4.
+    #[serde(default = "TunerConfig::default_reference")]
+    pub reference_hz: f32,
+    /// The number of samples analysed at once. Larger windows can detect lower notes, but respond
+    /// more slowly.
+    #[serde(default = "TunerConfig::default_window_size")]
+    pub window_size: usize,
+    /// When set, the detected note is sent as a `NoteOn`, and its deviation as a pitch bend where
+    /// the full range is ±50 cents.
+    pub midi_output: Option<MidiOutput>,
+}
+
+impl TunerConfig {
+    const DEFAULT_MUTE: bool = true;
+    const DEFAULT_REFERENCE: f32 = 440.0;
+    const DEFAULT_WINDOW_SIZE: usize = 2048;
+
+    fn default_mute() -> bool {
+        Self::DEFAULT_MUTE
+    }
+
+    fn default_reference() -> f32 {
+        Self::DEFAULT_REFERENCE
+    }
+
+    fn default_window_size() -> usize {
+        Self::DEFAULT_WINDOW_SIZE
+    }
+}
@@ -0,0 +1,87 @@
+use crate::{
+    audio_unit::{biquad::Kind, wah::Mode},
+    config::MidiSlider,
+};
+use serde::Deserialize;
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct WahConfig {
+    #[serde(default = "WahConfig::default_mode")]
+    pub mode: Mode,
+    /// `BandPass` or `LowPass`.
+    #[serde(default = "WahConfig::default_filter")]
+    pub filter: Kind,
+    #[serde(default = "WahConfig::default_min")]
+    pub min_hz: f32,
+    #[serde(default = "WahConfig::default_max")]
+    pub max_hz: f32,
+    #[serde(default = "WahConfig::default_q")]
+    pub q: f32,
+    #[serde(default = "WahConfig::default_q_max")]
+    pub max_q: f32,
+    #[serde(default = "WahConfig::default_sensitivity")]
+    pub sensitivity: f32,
+    #[serde(default = "WahConfig::default_sensitivity_max")]
+    pub max_sensitivity: f32,
+    #[serde(default = "WahConfig::default_attack")]
+    pub attack_ms: f32,
+    #[serde(default = "WahConfig::default_release")]
+    pub release_ms: f32,
+    /// The pedal which sweeps the filter in `Expression` mode.
+    pub expression: Option<MidiSlider>,
+    pub sensitivity_slider: Option<MidiSlider>,
+    pub q_slider: Option<MidiSlider>,
+}
+
+impl WahConfig {
+    const DEFAULT_MODE: Mode = Mode::Auto;
+    const DEFAULT_FILTER: Kind = Kind::BandPass;
+    const DEFAULT_MIN: f32 = 350.0;
+    const DEFAULT_MAX: f32 = 2_500.0;
+    const DEFAULT_Q: f32 = 4.0;
+    const DEFAULT_Q_MAX: f32 = 12.0;
+    const DEFAULT_SENSITIVITY: f32 = 4.0;
+    const DEFAULT_SENSITIVITY_MAX: f32 = 20.0;
+    const DEFAULT_ATTACK: f32 = 5.0;
+    const DEFAULT_RELEASE: f32 = 80.0;
+
+    fn default_mode() -> Mode {
+        Self::DEFAULT_MODE
+    }
+
+    fn default_filter() -> Kind {
+        Self::DEFAULT_FILTER
+    }
+
+    fn default_min() -> f32 {
+        Self::DEFAULT_MIN
+    }
+
+    fn default_max() -> f32 {
+        Self::DEFAULT_MAX
+    }
+
+    fn default_q() -> f32 {
+        Self::DEFAULT_Q
+    }
+
+    fn default_q_max() -> f32 {
+        Self::DEFAULT_Q_MAX
+    }
+
+    fn default_sensitivity() -> f32 {
+        Self::DEFAULT_SENSITIVITY
+    }
+
+    fn default_sensitivity_max() -> f32 {
+        Self::DEFAULT_SENSITIVITY_MAX
+    }
+
+    fn default_attack() -> f32 {
+        Self::DEFAULT_ATTACK
+    }
+
+    fn default_release() -> f32 {
+        Self::DEFAULT_RELEASE
+    }
+}
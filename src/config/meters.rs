@@ -0,0 +1,28 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct Meters {
+    /// Also measures the level after each effect, not just the pipeline's input and output.
+    #[serde(default)]
+    pub between_effects: bool,
+    /// How often the levels are printed, when the terminal UI isn't showing. 0 turns it off.
+    #[serde(default = "Meters::default_print_interval_ms")]
+    pub print_interval_ms: u32,
+}
+
+impl Meters {
+    const DEFAULT_PRINT_INTERVAL_MS: u32 = 5_000;
+
+    fn default_print_interval_ms() -> u32 {
+        Self::DEFAULT_PRINT_INTERVAL_MS
+    }
+}
+
+impl Default for Meters {
+    fn default() -> Self {
+        Self {
+            between_effects: false,
+            print_interval_ms: Self::default_print_interval_ms(),
+        }
+    }
+}
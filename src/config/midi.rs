@@ -2,17 +2,11 @@ use serde::{Deserialize, Deserializer};
 use std::convert::TryInto;
 use wmidi::{Channel, ControlFunction, Note, U7};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct Midi {
     pub port: Option<String>,
 }
 
-impl Default for Midi {
-    fn default() -> Self {
-        Self { port: None }
-    }
-}
-
 #[derive(Copy, Clone, Debug, Deserialize)]
 pub struct NoteOn {
     #[serde(deserialize_with = "deserialize_channel")]
@@ -27,6 +21,21 @@ impl NoteOn {
     }
 }
 
+/// A port to send MIDI messages to, and the channel to send them on.
+#[derive(Clone, Debug, Deserialize)]
+pub struct MidiOutput {
+    pub port: String,
+    #[serde(deserialize_with = "deserialize_channel")]
+    pub channel: Channel,
+}
+
+/// Every note played on a channel, eg. for effects which follow the pitch of a keyboard.
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct MidiNotes {
+    #[serde(deserialize_with = "deserialize_channel")]
+    pub channel: Channel,
+}
+
 #[derive(Copy, Clone, Debug, Deserialize)]
 pub struct MidiSlider {
     #[serde(deserialize_with = "deserialize_channel")]
@@ -1,10 +1,22 @@
 mod audio;
 mod effect;
+mod meters;
 mod midi;
+mod subdivision;
+mod tempo;
 
 pub use audio::Audio;
-pub use effect::{DelayConfig, Effect, LooperConfig};
-pub use midi::{Midi, MidiSlider, NoteOn};
+pub use effect::{
+    BitcrusherConfig, CompressorConfig, DelayConfig, DriveConfig, Effect, EqBandConfig, EqConfig,
+    FreezeConfig, GateConfig, GranularConfig, LimiterConfig, LooperCommandsConfig, LooperConfig,
+    LooperTrackConfig, ModulationConfig, ModulationKind, PhaserConfig, PitchShiftConfig,
+    PitchShiftVoiceConfig, RingModulatorConfig, SampleRateReducerConfig, SidechainConfig,
+    TapConfig, TapeConfig, TremoloConfig, TunerConfig, WahConfig,
+};
+pub use meters::Meters;
+pub use midi::{Midi, MidiNotes, MidiOutput, MidiSlider, NoteOn};
+pub use subdivision::Subdivision;
+pub use tempo::Tempo;
 
 use crate::Result;
 use serde::Deserialize;
@@ -15,6 +27,10 @@ pub struct Config {
     pub audio: Audio,
     #[serde(default)]
     pub midi: Midi,
+    #[serde(default)]
+    pub tempo: Tempo,
+    #[serde(default)]
+    pub meters: Meters,
     pub effects: Vec<Effect>,
 }
 
@@ -29,6 +45,8 @@ impl Default for Config {
         Self {
             audio: Audio::default(),
             midi: Midi::default(),
+            tempo: Tempo::default(),
+            meters: Meters::default(),
             effects: vec![Effect::Transparent],
         }
     }
@@ -0,0 +1,35 @@
+use serde::Deserialize;
+
+/// A note length, relative to a tapped beat (which is treated as a quarter note).
+#[derive(Copy, Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+pub enum Subdivision {
+    Whole,
+    Half,
+    DottedQuarter,
+    #[default]
+    Quarter,
+    QuarterTriplet,
+    DottedEighth,
+    Eighth,
+    EighthTriplet,
+    Sixteenth,
+    SixteenthTriplet,
+}
+
+impl Subdivision {
+    /// The length of the note, in beats.
+    pub fn beats(self) -> f32 {
+        match self {
+            Subdivision::Whole => 4.0,
+            Subdivision::Half => 2.0,
+            Subdivision::DottedQuarter => 1.5,
+            Subdivision::Quarter => 1.0,
+            Subdivision::QuarterTriplet => 2.0 / 3.0,
+            Subdivision::DottedEighth => 0.75,
+            Subdivision::Eighth => 0.5,
+            Subdivision::EighthTriplet => 1.0 / 3.0,
+            Subdivision::Sixteenth => 0.25,
+            Subdivision::SixteenthTriplet => 1.0 / 6.0,
+        }
+    }
+}
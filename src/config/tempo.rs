@@ -0,0 +1,11 @@
+use crate::config::NoteOn;
+use serde::Deserialize;
+
+/// The tempo shared by the whole pipeline, which effects can choose to follow.
+#[derive(Debug, Default, Deserialize)]
+pub struct Tempo {
+    pub tap_tempo: Option<NoteOn>,
+    /// Follows the MIDI clock on the input port.
+    #[serde(default)]
+    pub midi_clock: bool,
+}
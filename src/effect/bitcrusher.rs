@@ -0,0 +1,74 @@
+use crate::{
+    audio::midi,
+    audio_unit::{self, bitcrusher, AudioUnit},
+    config::BitcrusherConfig,
+    effect::{Effect, SharedParameters},
+    Result,
+};
+use anyhow::anyhow;
+use std::sync::mpsc::Sender;
+
+pub struct Bitcrusher {
+    config: BitcrusherConfig,
+    unit: audio_unit::Bitcrusher,
+    messages: Sender<bitcrusher::Message>,
+    parameters: SharedParameters,
+}
+
+impl Bitcrusher {
+    const MAX_BITS: f32 = 24.0;
+
+    pub fn new(config: BitcrusherConfig) -> Result<Self> {
+        Self::validate_config(&config)?;
+
+        let (unit, messages) = audio_unit::Bitcrusher::new(config.bits, config.dither);
+
+        let parameters = SharedParameters::new(&[("bits", config.bits)]);
+
+        Ok(Self {
+            config,
+            unit,
+            messages,
+            parameters,
+        })
+    }
+
+    fn validate_config(config: &BitcrusherConfig) -> Result<()> {
+        if config.min_bits >= 1.0
+            && config.min_bits <= config.max_bits
+            && config.max_bits <= Self::MAX_BITS
+            && (config.min_bits..=config.max_bits).contains(&config.bits)
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid bitcrusher config: {:#?}", config))
+        }
+    }
+
+    fn handle_midi_messages(&mut self, messages: &[midi::Message]) -> Result<()> {
+        if let Some(bits) = self.config.bits_slider.and_then(|slider| {
+            midi::latest_slider_value(slider, self.config.min_bits, self.config.max_bits, messages)
+        }) {
+            self.messages.send(bitcrusher::Message::SetBits(bits))?;
+            self.parameters.set("bits", bits);
+        }
+
+        Ok(())
+    }
+}
+
+impl Effect for Bitcrusher {
+    fn process(
+        &mut self,
+        midi_messages: &[midi::Message],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        self.handle_midi_messages(midi_messages)?;
+        self.unit.process(input, output)
+    }
+
+    fn parameters(&self) -> SharedParameters {
+        self.parameters.clone()
+    }
+}
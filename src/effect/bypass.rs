@@ -0,0 +1,29 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Which effects in a pipeline are bypassed, passing their input straight through. It can be
+/// changed from another thread while the pipeline runs.
+#[derive(Clone, Debug, Default)]
+pub struct Bypass(Arc<Vec<AtomicBool>>);
+
+impl Bypass {
+    pub fn new(effect_count: usize) -> Self {
+        Self(Arc::new(
+            (0..effect_count).map(|_| AtomicBool::new(false)).collect(),
+        ))
+    }
+
+    pub fn is_bypassed(&self, index: usize) -> bool {
+        self.0
+            .get(index)
+            .is_some_and(|bypassed| bypassed.load(Ordering::Relaxed))
+    }
+
+    pub fn toggle(&self, index: usize) {
+        if let Some(bypassed) = self.0.get(index) {
+            bypassed.fetch_xor(true, Ordering::Relaxed);
+        }
+    }
+}
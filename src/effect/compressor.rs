@@ -0,0 +1,119 @@
+use crate::{
+    audio::midi,
+    audio_unit::{self, compressor},
+    config::CompressorConfig,
+    effect::{sidechain, Effect, SharedParameters, Sidechains},
+    Result,
+};
+use anyhow::anyhow;
+use cpal::StreamConfig;
+use std::sync::mpsc::Sender;
+
+pub struct Compressor {
+    config: CompressorConfig,
+    unit: audio_unit::Compressor,
+    sidechain: Option<sidechain::Buffer>,
+    messages: Sender<compressor::Message>,
+    parameters: SharedParameters,
+}
+
+impl Compressor {
+    pub fn new(
+        config: CompressorConfig,
+        stream_config: &StreamConfig,
+        sidechains: &mut Sidechains,
+    ) -> Result<Self> {
+        Self::validate_config(&config)?;
+
+        let parameters = compressor::Parameters {
+            threshold_db: config.threshold_db,
+            ratio: config.ratio,
+            knee_db: config.knee_db,
+            attack_ms: config.attack_ms,
+            release_ms: config.release_ms,
+            makeup_db: config.makeup_db,
+        };
+        let (unit, messages) = audio_unit::Compressor::new(stream_config, parameters);
+        let sidechain = config
+            .sidechain
+            .as_ref()
+            .map(|name| sidechains.buffer(name));
+
+        let parameters = SharedParameters::new(&[
+            ("threshold_db", config.threshold_db),
+            ("ratio", config.ratio),
+            ("makeup_db", config.makeup_db),
+        ]);
+
+        Ok(Self {
+            config,
+            unit,
+            sidechain,
+            messages,
+            parameters,
+        })
+    }
+
+    fn validate_config(config: &CompressorConfig) -> Result<()> {
+        if config.ratio >= 1.0
+            && config.ratio <= config.max_ratio
+            && config.knee_db >= 0.0
+            && config.min_threshold_db <= 0.0
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid compressor config: {:#?}", config))
+        }
+    }
+
+    fn handle_midi_messages(&mut self, messages: &[midi::Message]) -> Result<()> {
+        if let Some(threshold_db) = self.config.threshold_slider.and_then(|slider| {
+            midi::latest_slider_value(slider, self.config.min_threshold_db, 0.0, messages)
+        }) {
+            self.messages
+                .send(compressor::Message::SetThreshold(threshold_db))?;
+            self.parameters.set("threshold_db", threshold_db);
+        }
+
+        if let Some(ratio) = self.config.ratio_slider.and_then(|slider| {
+            midi::latest_slider_value(slider, 1.0, self.config.max_ratio, messages)
+        }) {
+            self.messages.send(compressor::Message::SetRatio(ratio))?;
+            self.parameters.set("ratio", ratio);
+        }
+
+        if let Some(makeup_db) = self.config.makeup_slider.and_then(|slider| {
+            midi::latest_slider_value(slider, 0.0, self.config.max_makeup_db, messages)
+        }) {
+            self.messages
+                .send(compressor::Message::SetMakeup(makeup_db))?;
+            self.parameters.set("makeup_db", makeup_db);
+        }
+
+        Ok(())
+    }
+}
+
+impl Effect for Compressor {
+    fn process(
+        &mut self,
+        midi_messages: &[midi::Message],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        self.handle_midi_messages(midi_messages)?;
+
+        let unit = &mut self.unit;
+
+        match &self.sidechain {
+            Some(sidechain) => sidechain.read_or(input, |sidechain| {
+                unit.process_with_sidechain(sidechain, input, output)
+            })?,
+            None => unit.process_with_sidechain(input, input, output),
+        }
+    }
+
+    fn parameters(&self) -> SharedParameters {
+        self.parameters.clone()
+    }
+}
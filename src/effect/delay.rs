@@ -1,57 +1,238 @@
 use super::tap_tempo::TapTempo;
 use crate::{
     audio::midi,
-    audio_unit::{self, delay::Message, AudioUnit},
-    config::DelayConfig,
-    effect::Effect,
+    audio_unit::{self, biquad, delay::Message, tape_echo, AudioUnit},
+    config::{DelayConfig, TapConfig, TapeConfig},
+    effect::{Effect, SharedParameters, SharedTempo, Tempo},
+    util::log,
     Result,
 };
 use anyhow::anyhow;
 use cpal::StreamConfig;
 use std::sync::mpsc::Sender;
+use wmidi::MidiMessage;
+
+struct Tap {
+    config: TapConfig,
+    messages: Sender<Message>,
+}
+
+/// The unit which makes the repeats, and how to control it.
+enum Repeats {
+    Taps(Vec<Tap>),
+    Tape {
+        messages: Sender<tape_echo::Message>,
+        combination: usize,
+    },
+}
 
 pub struct Delay {
     config: DelayConfig,
     tap_tempo: Option<TapTempo>,
-    split: audio_unit::Split,
-    message_senders: Vec<Sender<Message>>,
+    tempo: SharedTempo,
+    last_tempo: Option<Tempo>,
+    is_reversed: bool,
+    unit: audio_unit::Boxed,
+    repeats: Repeats,
+    parameters: SharedParameters,
 }
 
 impl Delay {
-    pub fn new(config: DelayConfig, stream_config: &StreamConfig) -> Result<Self> {
+    const TAPE_TONE_HZ: f32 = 3_000.0;
+
+    pub fn new(
+        config: DelayConfig,
+        stream_config: &StreamConfig,
+        tempo: &SharedTempo,
+    ) -> Result<Self> {
         Self::validate_config(&config)?;
 
         let tap_tempo = config.tap_tempo.map(TapTempo::new);
 
+        let (unit, repeats) = match &config.tape {
+            Some(tape) => Self::tape_echo(&config, tape, stream_config),
+            None => Self::taps(&config, stream_config)?,
+        };
+
+        let parameters = SharedParameters::new(&[("delay_ms", config.delay_ms as f32)]);
+
+        Ok(Self {
+            config,
+            tap_tempo,
+            tempo: tempo.clone(),
+            last_tempo: None,
+            is_reversed: false,
+            unit,
+            repeats,
+            parameters,
+        })
+    }
+
+    /// A separate delay for each repeat, mixed with the dry signal.
+    fn taps(
+        config: &DelayConfig,
+        stream_config: &StreamConfig,
+    ) -> Result<(audio_unit::Boxed, Repeats)> {
         let mut audio_units = vec![];
-        let mut message_senders = vec![];
+        let mut taps = vec![];
+
+        for (n, tap) in Self::tap_configs(config).into_iter().enumerate() {
+            let n = n as u32;
+            let delay = Self::delay_for_tap(&tap, config.delay_ms);
+            let max_delay = Self::max_delay_for_tap(&tap, config.max_delay_ms);
+            let (delay_unit, messages) =
+                audio_unit::Delay::new(stream_config, delay, max_delay, config.reverse.is_some())?;
+            let gain_unit = audio_unit::Gain::new(tap.level).boxed();
 
-        for n in 0..config.num {
-            let delay = Self::delay_for_index(config.delay_ms, n);
-            let max_delay = config.max_delay_ms * (n + 1);
-            let (delay_unit, messages) = audio_unit::Delay::new(stream_config, delay, max_delay)?;
-            let gain_unit = audio_unit::Gain::new(config.level.powi(n as i32)).boxed();
-            let pipeline = audio_unit::Pipeline::new(vec![delay_unit.boxed(), gain_unit])?.boxed();
+            let mut tap_units = vec![delay_unit.boxed(), gain_unit];
+            tap_units.extend(Self::tone_units_for_index(config, stream_config, n));
 
-            audio_units.insert(audio_units.len(), pipeline);
-            message_senders.insert(message_senders.len(), messages);
+            // centered taps keep the input's stereo image, rather than being mixed down
+            let pan = Self::pan_for_index(config, &tap, n);
+            if pan != 0.0 {
+                tap_units.push(audio_unit::Pan::new(stream_config, pan).boxed());
+            }
+
+            let pipeline = audio_unit::Pipeline::new(tap_units)?.boxed();
+
+            audio_units.push(pipeline);
+            taps.push(Tap {
+                config: tap,
+                messages,
+            });
         }
 
         let transparent = audio_unit::Transparent::new().boxed();
-        audio_units.insert(audio_units.len(), transparent);
+        audio_units.push(transparent);
 
         let split = audio_unit::Split::new(audio_units)?;
 
-        Ok(Self {
-            config,
-            tap_tempo,
-            split,
-            message_senders,
-        })
+        Ok((split.boxed(), Repeats::Taps(taps)))
+    }
+
+    /// The configured taps, or `num` repeats a beat apart, each `level` times quieter than the
+    /// last.
+    fn tap_configs(config: &DelayConfig) -> Vec<TapConfig> {
+        match &config.taps {
+            Some(taps) => taps.clone(),
+            None => (0..config.num)
+                .map(|n| TapConfig {
+                    delay_ms: None,
+                    beats: Some((n + 1) as f32),
+                    subdivision: None,
+                    level: config.level.powi(n as i32),
+                    pan: 0.0,
+                })
+                .collect(),
+        }
+    }
+
+    fn delay_for_tap(tap: &TapConfig, beat_ms: u32) -> u32 {
+        match tap.beats() {
+            Some(beats) => (beat_ms as f32 * beats).round() as u32,
+            None => tap.delay_ms.unwrap_or(beat_ms),
+        }
+    }
+
+    /// The longest the tap can be, once the beat is stretched to `max_delay_ms`.
+    fn max_delay_for_tap(tap: &TapConfig, max_delay_ms: u32) -> u32 {
+        match tap.beats() {
+            Some(beats) => (max_delay_ms as f32 * beats).ceil() as u32,
+            None => tap.delay_ms.unwrap_or(max_delay_ms),
+        }
+    }
+
+    fn pan_for_index(config: &DelayConfig, tap: &TapConfig, index: u32) -> f32 {
+        match (config.ping_pong, index % 2) {
+            (true, 0) => -1.0,
+            (true, _) => 1.0,
+            (false, _) => tap.pan,
+        }
+    }
+
+    fn tape_echo(
+        config: &DelayConfig,
+        tape: &TapeConfig,
+        stream_config: &StreamConfig,
+    ) -> (audio_unit::Boxed, Repeats) {
+        let parameters = tape_echo::Parameters {
+            delay_ms: config.delay_ms as f32,
+            heads: tape.heads.clone(),
+            active_heads: tape.combinations[tape.combination].clone(),
+            feedback: config.level,
+            saturation: tape.saturation,
+            tone_hz: config.tone_hz.unwrap_or(Self::TAPE_TONE_HZ),
+            wow_rate_hz: tape.wow_rate_hz,
+            wow_depth_ms: tape.wow_depth_ms,
+            flutter_rate_hz: tape.flutter_rate_hz,
+            flutter_depth_ms: tape.flutter_depth_ms,
+        };
+        let (tape_echo, messages) =
+            audio_unit::TapeEcho::new(stream_config, parameters, config.max_delay_ms as f32);
+
+        (
+            tape_echo.boxed(),
+            Repeats::Tape {
+                messages,
+                combination: tape.combination,
+            },
+        )
+    }
+
+    /// Each repeat passes through one more low pass filter than the last, so that the repeats get
+    /// progressively darker.
+    fn tone_units_for_index(
+        config: &DelayConfig,
+        stream_config: &StreamConfig,
+        index: u32,
+    ) -> Vec<audio_unit::Boxed> {
+        let tone_hz = match config.tone_hz {
+            Some(tone_hz) => tone_hz,
+            None => return vec![],
+        };
+
+        let parameters = biquad::Parameters::new(
+            biquad::Kind::LowPass,
+            tone_hz,
+            biquad::Parameters::BUTTERWORTH_Q,
+            0.0,
+        );
+
+        (0..=index)
+            .map(|_| audio_unit::Biquad::new(stream_config, parameters).0.boxed())
+            .collect()
     }
 
     fn validate_config(config: &DelayConfig) -> Result<()> {
-        if config.delay_ms * config.num <= config.max_delay_ms {
+        let is_valid = match &config.tape {
+            Some(tape) => {
+                config.delay_ms <= config.max_delay_ms
+                    && config.reverse.is_none()
+                    && tape.saturation > 0.0
+                    && !config.ping_pong
+                    && config.taps.is_none()
+                    && !tape.heads.is_empty()
+                    && tape.heads.iter().all(|head| *head > 0.0)
+                    && tape.combination < tape.combinations.len()
+                    && tape
+                        .combinations
+                        .iter()
+                        .flatten()
+                        .all(|index| *index < tape.heads.len())
+            }
+            None => {
+                let taps = Self::tap_configs(config);
+
+                !taps.is_empty()
+                    && taps.iter().all(|tap| {
+                        tap.has_one_time()
+                            && (-1.0..=1.0).contains(&tap.pan)
+                            && Self::delay_for_tap(tap, config.delay_ms) <= config.max_delay_ms
+                    })
+            }
+        };
+
+        if is_valid {
             Ok(())
         } else {
             Err(anyhow!("Invalid delay config: {:#?}", config))
@@ -63,6 +244,77 @@ impl Delay {
             self.set_delay(delay)?;
         }
 
+        if let Some(delay) = self.delay_from_shared_tempo()? {
+            self.set_delay(delay)?;
+        }
+
+        let reverse = self.config.reverse;
+        let next_combination = self
+            .config
+            .tape
+            .as_ref()
+            .and_then(|tape| tape.next_combination);
+
+        for message in messages {
+            match message.message {
+                MidiMessage::NoteOn(channel, note, _)
+                    if reverse.is_some_and(|reverse| {
+                        channel == reverse.channel && note == reverse.note
+                    }) =>
+                {
+                    self.toggle_reverse()?;
+                }
+                MidiMessage::NoteOn(channel, note, _)
+                    if next_combination.is_some_and(|next_combination| {
+                        channel == next_combination.channel && note == next_combination.note
+                    }) =>
+                {
+                    self.select_next_combination()?;
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn toggle_reverse(&mut self) -> Result<()> {
+        let taps = match &self.repeats {
+            Repeats::Taps(taps) => taps,
+            Repeats::Tape { .. } => return Ok(()),
+        };
+
+        self.is_reversed = !self.is_reversed;
+        log::print(format!(
+            "delay: reverse {}",
+            if self.is_reversed { "on" } else { "off" }
+        ));
+
+        for tap in taps {
+            tap.messages.send(Message::SetReverse(self.is_reversed))?;
+        }
+
+        Ok(())
+    }
+
+    fn select_next_combination(&mut self) -> Result<()> {
+        let combinations = match &self.config.tape {
+            Some(tape) => &tape.combinations,
+            None => return Ok(()),
+        };
+
+        if let Repeats::Tape {
+            messages,
+            combination,
+        } = &mut self.repeats
+        {
+            *combination = (*combination + 1) % combinations.len();
+            let heads = combinations[*combination].clone();
+
+            log::print(format!("delay: heads {:?}", heads));
+            messages.send(tape_echo::Message::SetActiveHeads(heads))?;
+        }
+
         Ok(())
     }
 
@@ -92,18 +344,34 @@ impl Delay {
         Some(tempo.beat_duration_as_ms())
     }
 
-    fn set_delay(&mut self, delay_ms: u32) -> Result<()> {
-        for (i, sender) in self.message_senders.iter().enumerate() {
-            let delay_ms = Self::delay_for_index(delay_ms, i as u32);
-            let message = Message::SetDelay(delay_ms);
-            sender.send(message)?;
+    fn delay_from_shared_tempo(&mut self) -> Result<Option<u32>> {
+        if !self.config.follow_tempo {
+            return Ok(None);
         }
 
-        Ok(())
+        let tempo = self.tempo.changed(&mut self.last_tempo)?;
+
+        Ok(tempo.map(|tempo| tempo.beat_duration_as_ms().min(self.config.max_delay_ms)))
     }
 
-    fn delay_for_index(base_delay: u32, index: u32) -> u32 {
-        base_delay * (index + 1)
+    fn set_delay(&mut self, delay_ms: u32) -> Result<()> {
+        self.parameters.set("delay_ms", delay_ms as f32);
+
+        match &self.repeats {
+            Repeats::Taps(taps) => {
+                // taps with a fixed time ignore the tempo
+                for tap in taps.iter().filter(|tap| tap.config.beats().is_some()) {
+                    let delay_ms = Self::delay_for_tap(&tap.config, delay_ms);
+                    let message = Message::SetDelay(delay_ms);
+                    tap.messages.send(message)?;
+                }
+            }
+            Repeats::Tape { messages, .. } => {
+                messages.send(tape_echo::Message::SetDelay(delay_ms as f32))?;
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -115,6 +383,10 @@ impl Effect for Delay {
         output: &mut [f32],
     ) -> Result<()> {
         self.handle_midi_messages(midi_messages)?;
-        self.split.process(input, output)
+        self.unit.process(input, output)
+    }
+
+    fn parameters(&self) -> SharedParameters {
+        self.parameters.clone()
     }
 }
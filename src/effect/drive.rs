@@ -0,0 +1,141 @@
+use crate::{
+    audio::midi,
+    audio_unit::{self, biquad, drive, one_pole, AudioUnit},
+    config::DriveConfig,
+    effect::{Effect, SharedParameters},
+    Result,
+};
+use anyhow::anyhow;
+use cpal::StreamConfig;
+use std::sync::mpsc::Sender;
+
+pub struct Drive {
+    config: DriveConfig,
+    pipeline: audio_unit::Pipeline,
+    drive_messages: Sender<drive::Message>,
+    tone_messages: Sender<biquad::Message>,
+    parameters: SharedParameters,
+}
+
+impl Drive {
+    const VALID_OVERSAMPLING: [usize; 4] = [1, 2, 4, 8];
+    const DC_BLOCKER_HZ: f32 = 10.0;
+
+    pub fn new(config: DriveConfig, stream_config: &StreamConfig) -> Result<Self> {
+        Self::validate_config(&config)?;
+
+        let (pre_filter, _) = audio_unit::Biquad::new(
+            stream_config,
+            biquad::Parameters::new(
+                biquad::Kind::HighPass,
+                config.pre_filter_hz,
+                biquad::Parameters::BUTTERWORTH_Q,
+                0.0,
+            ),
+        );
+        let (drive, drive_messages) = audio_unit::Drive::new(
+            stream_config,
+            config.shape,
+            config.drive,
+            config.level,
+            config.oversampling,
+        );
+        // asymmetric shapes add a DC offset, which needs to be removed before the output
+        let (dc_blocker, _) =
+            audio_unit::OnePole::new(stream_config, one_pole::Kind::HighPass, Self::DC_BLOCKER_HZ);
+        let (tone, tone_messages) = audio_unit::Biquad::new(
+            stream_config,
+            biquad::Parameters::new(
+                biquad::Kind::LowPass,
+                config.tone_hz,
+                biquad::Parameters::BUTTERWORTH_Q,
+                0.0,
+            ),
+        );
+
+        let pipeline = audio_unit::Pipeline::new(vec![
+            pre_filter.boxed(),
+            drive.boxed(),
+            dc_blocker.boxed(),
+            tone.boxed(),
+        ])?;
+
+        let parameters = SharedParameters::new(&[
+            ("drive", config.drive),
+            ("level", config.level),
+            ("tone_hz", config.tone_hz),
+        ]);
+
+        Ok(Self {
+            config,
+            pipeline,
+            drive_messages,
+            tone_messages,
+            parameters,
+        })
+    }
+
+    fn validate_config(config: &DriveConfig) -> Result<()> {
+        if Self::VALID_OVERSAMPLING.contains(&config.oversampling)
+            && config.min_drive <= config.max_drive
+            && config.min_tone_hz > 0.0
+            && config.min_tone_hz <= config.max_tone_hz
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid drive config: {:#?}", config))
+        }
+    }
+
+    fn handle_midi_messages(&mut self, messages: &[midi::Message]) -> Result<()> {
+        if let Some(drive) = self.config.drive_slider.and_then(|slider| {
+            midi::latest_slider_value(
+                slider,
+                self.config.min_drive,
+                self.config.max_drive,
+                messages,
+            )
+        }) {
+            self.drive_messages.send(drive::Message::SetDrive(drive))?;
+            self.parameters.set("drive", drive);
+        }
+
+        if let Some(level) = self.config.level_slider.and_then(|slider| {
+            midi::latest_slider_value(slider, 0.0, self.config.max_level, messages)
+        }) {
+            self.drive_messages.send(drive::Message::SetLevel(level))?;
+            self.parameters.set("level", level);
+        }
+
+        if let Some(tone) = self.config.tone_slider.and_then(|slider| {
+            midi::latest_slider_value_exponential(
+                slider,
+                self.config.min_tone_hz,
+                self.config.max_tone_hz,
+                messages,
+            )
+        }) {
+            self.tone_messages
+                .send(biquad::Message::SetFrequency(tone))?;
+            self.parameters.set("tone_hz", tone);
+        }
+
+        Ok(())
+    }
+}
+
+impl Effect for Drive {
+    fn process(
+        &mut self,
+        midi_messages: &[midi::Message],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        self.handle_midi_messages(midi_messages)?;
+        self.pipeline.process(input, output)
+    }
+
+    fn parameters(&self) -> SharedParameters {
+        self.parameters.clone()
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{
+    audio::midi,
+    audio_unit::{self, biquad, AudioUnit},
+    config::{EqBandConfig, EqConfig},
+    effect::{Effect, Parameter, SharedParameters},
+    Result,
+};
+use anyhow::anyhow;
+use cpal::StreamConfig;
+use std::sync::mpsc::Sender;
+
+struct Band {
+    config: EqBandConfig,
+    messages: Sender<biquad::Message>,
+    frequency: Parameter,
+    gain_db: Parameter,
+    q: Parameter,
+}
+
+impl Band {
+    fn handle_midi_messages(&self, messages: &[midi::Message]) -> Result<()> {
+        if let Some(frequency) = self.config.frequency_slider.and_then(|slider| {
+            midi::latest_slider_value_exponential(
+                slider,
+                self.config.min_frequency,
+                self.config.max_frequency,
+                messages,
+            )
+        }) {
+            self.messages
+                .send(biquad::Message::SetFrequency(frequency))?;
+            self.frequency.set(frequency);
+        }
+
+        if let Some(gain_db) = self.config.gain_slider.and_then(|slider| {
+            midi::latest_slider_value(
+                slider,
+                self.config.min_gain_db,
+                self.config.max_gain_db,
+                messages,
+            )
+        }) {
+            self.messages.send(biquad::Message::SetGain(gain_db))?;
+            self.gain_db.set(gain_db);
+        }
+
+        if let Some(q) = self.config.q_slider.and_then(|slider| {
+            midi::latest_slider_value(slider, self.config.min_q, self.config.max_q, messages)
+        }) {
+            self.messages.send(biquad::Message::SetQ(q))?;
+            self.q.set(q);
+        }
+
+        Ok(())
+    }
+}
+
+pub struct Eq {
+    bands: Vec<Band>,
+    pipeline: audio_unit::Pipeline,
+    parameters: SharedParameters,
+}
+
+impl Eq {
+    pub fn new(config: EqConfig, stream_config: &StreamConfig) -> Result<Self> {
+        Self::validate_config(&config)?;
+
+        let mut audio_units = vec![];
+        let mut bands = vec![];
+        let mut parameters = SharedParameters::default();
+
+        for (index, band_config) in config.bands.into_iter().enumerate() {
+            let filter = biquad::Parameters::new(
+                band_config.kind,
+                band_config.frequency,
+                band_config.q,
+                band_config.gain_db,
+            );
+            let (biquad, messages) = audio_unit::Biquad::new(stream_config, filter);
+
+            audio_units.push(biquad.boxed());
+            // bands are numbered, since each has the same parameters
+            let number = index + 1;
+            bands.push(Band {
+                config: band_config,
+                messages,
+                frequency: parameters.add(format!("frequency {}", number), band_config.frequency),
+                gain_db: parameters.add(format!("gain_db {}", number), band_config.gain_db),
+                q: parameters.add(format!("q {}", number), band_config.q),
+            });
+        }
+
+        let pipeline = audio_unit::Pipeline::new(audio_units)?;
+
+        Ok(Self {
+            bands,
+            pipeline,
+            parameters,
+        })
+    }
+
+    fn validate_config(config: &EqConfig) -> Result<()> {
+        let bands_are_valid = config.bands.iter().all(|band| {
+            band.min_frequency > 0.0
+                && band.min_frequency <= band.max_frequency
+                && band.min_gain_db <= band.max_gain_db
+                && band.min_q > 0.0
+                && band.min_q <= band.max_q
+        });
+
+        if !config.bands.is_empty() && bands_are_valid {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid EQ config: {:#?}", config))
+        }
+    }
+}
+
+impl Effect for Eq {
+    fn process(
+        &mut self,
+        midi_messages: &[midi::Message],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        for band in &self.bands {
+            band.handle_midi_messages(midi_messages)?;
+        }
+
+        self.pipeline.process(input, output)
+    }
+
+    fn parameters(&self) -> SharedParameters {
+        self.parameters.clone()
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{
+    audio::midi,
+    audio_unit::{self, freeze::Message, AudioUnit},
+    config::FreezeConfig,
+    effect::Effect,
+    Result,
+};
+use anyhow::anyhow;
+use cpal::StreamConfig;
+use std::sync::mpsc::Sender;
+use wmidi::MidiMessage;
+
+pub struct Freeze {
+    config: FreezeConfig,
+    is_frozen: bool,
+    split: audio_unit::Split,
+    messages: Sender<Message>,
+}
+
+impl Freeze {
+    const MIN_FRAME_SIZE: usize = 256;
+
+    pub fn new(config: FreezeConfig, stream_config: &StreamConfig) -> Result<Self> {
+        Self::validate_config(&config)?;
+
+        let (freeze, messages) =
+            audio_unit::Freeze::new(stream_config, config.frame_size, config.release_ms);
+        let gain = audio_unit::Gain::new(config.level);
+        let frozen = audio_unit::Pipeline::new(vec![freeze.boxed(), gain.boxed()])?;
+        let transparent = audio_unit::Transparent::new();
+
+        let split = audio_unit::Split::new(vec![frozen.boxed(), transparent.boxed()])?;
+
+        Ok(Self {
+            config,
+            is_frozen: false,
+            split,
+            messages,
+        })
+    }
+
+    fn validate_config(config: &FreezeConfig) -> Result<()> {
+        if config.frame_size >= Self::MIN_FRAME_SIZE && config.frame_size.is_power_of_two() {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid freeze config: {:#?}", config))
+        }
+    }
+
+    fn handle_midi_messages(&mut self, messages: &[midi::Message]) -> Result<()> {
+        let freeze = self.config.freeze;
+
+        for message in messages {
+            match message.message {
+                MidiMessage::NoteOn(channel, note, _)
+                    if channel == freeze.channel && note == freeze.note =>
+                {
+                    if self.config.latch && self.is_frozen {
+                        self.release()?;
+                    } else {
+                        self.freeze()?;
+                    }
+                }
+                MidiMessage::NoteOff(channel, note, _)
+                    if channel == freeze.channel && note == freeze.note && !self.config.latch =>
+                {
+                    self.release()?;
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn freeze(&mut self) -> Result<()> {
+        self.is_frozen = true;
+        Ok(self.messages.send(Message::Freeze)?)
+    }
+
+    fn release(&mut self) -> Result<()> {
+        self.is_frozen = false;
+        Ok(self.messages.send(Message::Release)?)
+    }
+}
+
+impl Effect for Freeze {
+    fn process(
+        &mut self,
+        midi_messages: &[midi::Message],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        self.handle_midi_messages(midi_messages)?;
+        self.split.process(input, output)
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{
+    audio::midi,
+    audio_unit::{self, gate},
+    config::GateConfig,
+    effect::{sidechain, Effect, SharedParameters, Sidechains},
+    Result,
+};
+use anyhow::anyhow;
+use cpal::StreamConfig;
+use std::sync::mpsc::Sender;
+
+pub struct Gate {
+    config: GateConfig,
+    unit: audio_unit::Gate,
+    sidechain: Option<sidechain::Buffer>,
+    messages: Sender<gate::Message>,
+    parameters: SharedParameters,
+}
+
+impl Gate {
+    pub fn new(
+        config: GateConfig,
+        stream_config: &StreamConfig,
+        sidechains: &mut Sidechains,
+    ) -> Result<Self> {
+        Self::validate_config(&config)?;
+
+        let parameters = gate::Parameters {
+            open_threshold_db: config.threshold_db,
+            close_threshold_db: config.threshold_db - config.hysteresis_db,
+            hold_ms: config.hold_ms,
+            attack_ms: config.attack_ms,
+            release_ms: config.release_ms,
+            range_db: config.range_db,
+        };
+        let (unit, messages) = audio_unit::Gate::new(stream_config, parameters);
+        let sidechain = config
+            .sidechain
+            .as_ref()
+            .map(|name| sidechains.buffer(name));
+
+        let parameters = SharedParameters::new(&[("threshold_db", config.threshold_db)]);
+
+        Ok(Self {
+            config,
+            unit,
+            sidechain,
+            messages,
+            parameters,
+        })
+    }
+
+    fn validate_config(config: &GateConfig) -> Result<()> {
+        if config.hysteresis_db >= 0.0
+            && config.hold_ms >= 0.0
+            && config.range_db <= 0.0
+            && config.min_threshold_db <= 0.0
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid gate config: {:#?}", config))
+        }
+    }
+
+    fn handle_midi_messages(&mut self, messages: &[midi::Message]) -> Result<()> {
+        if let Some(threshold_db) = self.config.threshold_slider.and_then(|slider| {
+            midi::latest_slider_value(slider, self.config.min_threshold_db, 0.0, messages)
+        }) {
+            self.messages
+                .send(gate::Message::SetThreshold(threshold_db))?;
+            self.parameters.set("threshold_db", threshold_db);
+        }
+
+        Ok(())
+    }
+}
+
+impl Effect for Gate {
+    fn process(
+        &mut self,
+        midi_messages: &[midi::Message],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        self.handle_midi_messages(midi_messages)?;
+
+        let unit = &mut self.unit;
+
+        match &self.sidechain {
+            Some(sidechain) => sidechain.read_or(input, |sidechain| {
+                unit.process_with_sidechain(sidechain, input, output)
+            })?,
+            None => unit.process_with_sidechain(input, input, output),
+        }
+    }
+
+    fn parameters(&self) -> SharedParameters {
+        self.parameters.clone()
+    }
+}
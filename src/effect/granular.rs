@@ -0,0 +1,157 @@
+use crate::{
+    audio::midi,
+    audio_unit::{self, granular, AudioUnit},
+    config::GranularConfig,
+    effect::{Effect, SharedParameters},
+    Result,
+};
+use anyhow::anyhow;
+use cpal::StreamConfig;
+use std::sync::mpsc::Sender;
+
+pub struct Granular {
+    config: GranularConfig,
+    unit: audio_unit::Granular,
+    messages: Sender<granular::Message>,
+    parameters: SharedParameters,
+}
+
+impl Granular {
+    const MIN_DENSITY: f32 = 0.5;
+    const MAX_PITCH: f32 = 12.0;
+    const MAX_PITCH_SPREAD: f32 = 12.0;
+    const MAX_FEEDBACK: f32 = 0.95;
+    // grains can be played up to two octaves up, which reads four grains' worth of the buffer
+    const MAX_PLAYBACK_RATE: f32 = 4.0;
+
+    pub fn new(config: GranularConfig, stream_config: &StreamConfig) -> Result<Self> {
+        Self::validate_config(&config)?;
+
+        let parameters = granular::Parameters {
+            density: config.density,
+            size_ms: config.size_ms,
+            position_ms: config.position_ms,
+            pitch: config.pitch,
+            pitch_spread: config.pitch_spread,
+            stereo_spread: config.stereo_spread,
+            feedback: config.feedback,
+            mix: config.mix,
+        };
+        let max_delay_ms = config.max_position_ms + config.max_size_ms * Self::MAX_PLAYBACK_RATE;
+        let (unit, messages) = audio_unit::Granular::new(stream_config, parameters, max_delay_ms);
+
+        let parameters = SharedParameters::new(&[
+            ("density", config.density),
+            ("size_ms", config.size_ms),
+            ("position_ms", config.position_ms),
+            ("pitch", config.pitch),
+            ("pitch_spread", config.pitch_spread),
+            ("feedback", config.feedback),
+            ("mix", config.mix),
+        ]);
+
+        Ok(Self {
+            config,
+            unit,
+            messages,
+            parameters,
+        })
+    }
+
+    fn validate_config(config: &GranularConfig) -> Result<()> {
+        if (Self::MIN_DENSITY..=config.max_density).contains(&config.density)
+            && config.min_size_ms > 0.0
+            && (config.min_size_ms..=config.max_size_ms).contains(&config.size_ms)
+            && (0.0..=config.max_position_ms).contains(&config.position_ms)
+            && config.pitch.abs() <= Self::MAX_PITCH
+            && (0.0..=Self::MAX_PITCH_SPREAD).contains(&config.pitch_spread)
+            && (0.0..=1.0).contains(&config.stereo_spread)
+            && (0.0..=Self::MAX_FEEDBACK).contains(&config.feedback)
+            && (0.0..=1.0).contains(&config.mix)
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid granular config: {:#?}", config))
+        }
+    }
+
+    fn handle_midi_messages(&mut self, messages: &[midi::Message]) -> Result<()> {
+        let config = self.config;
+        let slider = |slider: Option<_>, min, max| {
+            slider.and_then(|slider| midi::latest_slider_value(slider, min, max, messages))
+        };
+
+        if let Some(density) = config.density_slider.and_then(|slider| {
+            midi::latest_slider_value_exponential(
+                slider,
+                Self::MIN_DENSITY,
+                config.max_density,
+                messages,
+            )
+        }) {
+            self.messages.send(granular::Message::SetDensity(density))?;
+            self.parameters.set("density", density);
+        }
+
+        if let Some(size) = config.size_slider.and_then(|slider| {
+            midi::latest_slider_value_exponential(
+                slider,
+                config.min_size_ms,
+                config.max_size_ms,
+                messages,
+            )
+        }) {
+            self.messages.send(granular::Message::SetSize(size))?;
+            self.parameters.set("size_ms", size);
+        }
+
+        if let Some(position) = slider(config.position_slider, 0.0, config.max_position_ms) {
+            self.messages
+                .send(granular::Message::SetPosition(position))?;
+            self.parameters.set("position_ms", position);
+        }
+
+        if let Some(pitch) = slider(config.pitch_slider, -Self::MAX_PITCH, Self::MAX_PITCH) {
+            // snap to semitones, so that the cloud stays in tune
+            self.messages
+                .send(granular::Message::SetPitch(pitch.round()))?;
+            self.parameters.set("pitch", pitch.round());
+        }
+
+        if let Some(pitch_spread) = slider(config.pitch_spread_slider, 0.0, Self::MAX_PITCH_SPREAD)
+        {
+            self.messages
+                .send(granular::Message::SetPitchSpread(pitch_spread))?;
+            self.parameters.set("pitch_spread", pitch_spread);
+        }
+
+        if let Some(feedback) = slider(config.feedback_slider, 0.0, Self::MAX_FEEDBACK) {
+            self.messages
+                .send(granular::Message::SetFeedback(feedback))?;
+            self.parameters.set("feedback", feedback);
+        }
+
+        if let Some(mix) = slider(config.mix_slider, 0.0, 1.0) {
+            self.messages.send(granular::Message::SetMix(mix))?;
+            self.parameters.set("mix", mix);
+        }
+
+        Ok(())
+    }
+}
+
+impl Effect for Granular {
+    fn process(
+        &mut self,
+        midi_messages: &[midi::Message],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        self.handle_midi_messages(midi_messages)?;
+        self.unit.process(input, output)
+    }
+
+    fn parameters(&self) -> SharedParameters {
+        self.parameters.clone()
+    }
+}
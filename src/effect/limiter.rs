@@ -0,0 +1,78 @@
+use crate::{
+    audio::midi,
+    audio_unit::{self, limiter, AudioUnit},
+    config::LimiterConfig,
+    effect::{Effect, SharedParameters},
+    Result,
+};
+use anyhow::anyhow;
+use cpal::StreamConfig;
+use std::sync::mpsc::Sender;
+
+pub struct Limiter {
+    config: LimiterConfig,
+    unit: audio_unit::Limiter,
+    messages: Sender<limiter::Message>,
+    parameters: SharedParameters,
+}
+
+impl Limiter {
+    pub fn new(config: LimiterConfig, stream_config: &StreamConfig) -> Result<Self> {
+        Self::validate_config(&config)?;
+
+        let (unit, messages) = audio_unit::Limiter::new(
+            stream_config,
+            config.ceiling_db,
+            config.lookahead_ms,
+            config.release_ms,
+        );
+
+        let parameters = SharedParameters::new(&[("ceiling_db", config.ceiling_db)]);
+
+        Ok(Self {
+            config,
+            unit,
+            messages,
+            parameters,
+        })
+    }
+
+    fn validate_config(config: &LimiterConfig) -> Result<()> {
+        if config.ceiling_db <= 0.0
+            && config.min_ceiling_db <= config.ceiling_db
+            && config.lookahead_ms > 0.0
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid limiter config: {:#?}", config))
+        }
+    }
+
+    fn handle_midi_messages(&mut self, messages: &[midi::Message]) -> Result<()> {
+        if let Some(ceiling_db) = self.config.ceiling_slider.and_then(|slider| {
+            midi::latest_slider_value(slider, self.config.min_ceiling_db, 0.0, messages)
+        }) {
+            self.messages
+                .send(limiter::Message::SetCeiling(ceiling_db))?;
+            self.parameters.set("ceiling_db", ceiling_db);
+        }
+
+        Ok(())
+    }
+}
+
+impl Effect for Limiter {
+    fn process(
+        &mut self,
+        midi_messages: &[midi::Message],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        self.handle_midi_messages(midi_messages)?;
+        self.unit.process(input, output)
+    }
+
+    fn parameters(&self) -> SharedParameters {
+        self.parameters.clone()
+    }
+}
@@ -1,61 +1,258 @@
 use crate::{
     audio::midi,
-    audio_unit::{self, looper::Message, AudioUnit},
-    config::LooperConfig,
-    effect::Effect,
+    audio_unit::{
+        self,
+        looper::{Event, Message, Parameters, State, TrackIndex},
+        AudioUnit,
+    },
+    config::{LooperConfig, LooperTrackConfig, NoteOn},
+    effect::{Effect, SharedParameters, SharedTempo, Statuses, Tempo},
+    util::{log, wav},
     Result,
 };
+use anyhow::anyhow;
 use cpal::StreamConfig;
-use std::sync::mpsc::Sender;
-use wmidi::MidiMessage;
+use std::{
+    path::PathBuf,
+    sync::mpsc::{Receiver, Sender},
+};
+use wmidi::{MidiMessage, U7};
 
 pub struct Looper {
     config: LooperConfig,
+    tracks: Vec<LooperTrackConfig>,
     split: audio_unit::Split,
     messages: Sender<Message>,
+    events: Receiver<Event>,
+    sample_rate: u32,
+    tempo: SharedTempo,
+    last_tempo: Option<Tempo>,
+    /// When the latest beat of the tempo started, so that the looper can move to each new beat.
+    last_beat_start: Option<u64>,
+    midi_output: Option<Sender<MidiMessage<'static>>>,
+    parameters: SharedParameters,
 }
 
 impl Looper {
-    pub fn new(config: LooperConfig, stream_config: &StreamConfig) -> Result<Self> {
-        let (looper, messages) = audio_unit::Looper::new(stream_config, config.max_ms);
+    const ACTIVE_VELOCITY: U7 = U7::MAX;
+    const PLAYING_VELOCITY: U7 = U7::from_u8_lossy(64);
+
+    pub fn new(
+        config: LooperConfig,
+        stream_config: &StreamConfig,
+        tempo: &SharedTempo,
+        statuses: &mut Statuses,
+    ) -> Result<Self> {
+        let tracks = config.tracks();
+        Self::validate_config(&config, &tracks)?;
+
+        let parameters = Parameters {
+            max_buffer_ms: config.max_ms,
+            max_history_ms: config.max_history_ms,
+            levels: tracks.iter().map(|track| track.level).collect(),
+            quantize: config.quantize,
+            beats_per_bar: config.beats_per_bar as usize,
+            loop_beats: config.loop_beats.map(|loop_beats| loop_beats as usize),
+            crossfade_ms: config.crossfade_ms,
+            fade_ms: config.fade_ms,
+            feedback: config.feedback,
+        };
+        let (mut looper, messages, events) = audio_unit::Looper::new(stream_config, &parameters);
+
+        for (index, track) in tracks.iter().enumerate() {
+            if let Some(file) = &track.file {
+                looper.load(index, &wav::read(file, stream_config)?);
+                log::print(format!("looper: loaded {} into track {}", file, index + 1));
+            }
+        }
+
+        if config.save.is_some() {
+            looper.save_to(PathBuf::from(&config.save_directory));
+        }
+
+        statuses.loopers.push(looper.status());
+
+        let midi_output = config
+            .midi_output
+            .as_ref()
+            .map(|output| midi::connect_output(&output.port))
+            .transpose()?;
+
         let transparent = audio_unit::Transparent::new();
 
         let split = audio_unit::Split::new(vec![looper.boxed(), transparent.boxed()])?;
 
+        let parameters = SharedParameters::new(&[("feedback", config.feedback)]);
+
         Ok(Self {
             config,
+            tracks,
             split,
             messages,
+            events,
+            sample_rate: stream_config.sample_rate.0,
+            tempo: tempo.clone(),
+            last_tempo: None,
+            last_beat_start: None,
+            midi_output,
+            parameters,
         })
     }
 
+    fn validate_config(config: &LooperConfig, tracks: &[LooperTrackConfig]) -> Result<()> {
+        let is_valid = !tracks.is_empty()
+            && config.beats_per_bar > 0
+            && config.crossfade_ms >= 0.0
+            && config.fade_ms >= 0.0
+            && (0.0..=1.0).contains(&config.feedback)
+            && config.loop_beats.is_none_or(|loop_beats| loop_beats > 0);
+
+        if is_valid {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid looper config: {:#?}", config))
+        }
+    }
+
     fn handle_midi_messages(&mut self, messages: &[midi::Message]) -> Result<()> {
+        if let Some(feedback) = self
+            .config
+            .feedback_slider
+            .and_then(|slider| midi::latest_slider_value(slider, 0.0, 1.0, messages))
+        {
+            self.messages.send(Message::SetFeedback(feedback))?;
+            self.parameters.set("feedback", feedback);
+        }
+
         for message in messages {
-            match message.message {
-                MidiMessage::NoteOn(channel, note, _)
-                    if channel == self.config.toggle.channel && note == self.config.toggle.note =>
-                {
-                    self.toggle()?;
+            if let MidiMessage::NoteOn(channel, note, _) = message.message {
+                let is_note = |note_on: Option<NoteOn>| {
+                    note_on
+                        .is_some_and(|note_on| channel == note_on.channel && note == note_on.note)
+                };
+
+                if is_note(self.config.reverse) {
+                    self.messages.send(Message::ToggleReverse)?;
+                }
+
+                if is_note(self.config.save) {
+                    self.messages.send(Message::Save)?;
+                }
+
+                for (index, track) in self.tracks.iter().enumerate() {
+                    if let Some(message) = Self::track_message(track, index, is_note) {
+                        self.messages.send(message)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reports what happened in the looper during the last buffer.
+    fn handle_events(&mut self) -> Result<()> {
+        let events = self.events.try_iter().collect::<Vec<_>>();
+
+        for event in events {
+            match event {
+                Event::Tempo(frames_per_beat) => {
+                    let tempo = Tempo::from_frames(frames_per_beat, self.sample_rate);
+                    log::print(format!(
+                        "looper: tempo {:.1} bpm",
+                        60.0 * tempo.beat_frequency()
+                    ));
+
+                    self.tempo.set(tempo)?;
+                    // the looper is already in time with its own tempo
+                    self.last_tempo = Some(tempo);
+                    self.last_beat_start = Some(tempo.start_timestamp());
+                }
+                Event::State(index, state) => {
+                    log::print(format!("looper: {}", event));
+                    self.update_led(index, Self::led_velocity(state))?;
                 }
-                MidiMessage::NoteOn(channel, note, _)
-                    if channel == self.config.overdub.channel
-                        && note == self.config.overdub.note =>
-                {
-                    self.enable_overdub_mode()?;
+                Event::Cleared(index) => {
+                    log::print(format!("looper: {}", event));
+                    self.update_led(index, None)?;
                 }
-                _ => (),
+                _ => log::print(format!("looper: {}", event)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn led_velocity(state: State) -> Option<U7> {
+        match state {
+            State::Recording | State::Overdubbing => Some(Self::ACTIVE_VELOCITY),
+            State::Playing | State::PlayingAwaitingOverdub | State::PlayingOnce => {
+                Some(Self::PLAYING_VELOCITY)
             }
+            _ => None,
+        }
+    }
+
+    fn update_led(&self, index: TrackIndex, velocity: Option<U7>) -> Result<()> {
+        if let (Some(sender), Some(output), Some(track)) = (
+            &self.midi_output,
+            &self.config.midi_output,
+            self.tracks.get(index),
+        ) {
+            let channel = output.channel;
+            let note = track.toggle.note;
+
+            sender.send(match velocity {
+                Some(velocity) => MidiMessage::NoteOn(channel, note, velocity),
+                None => MidiMessage::NoteOff(channel, note, U7::MIN),
+            })?;
         }
 
         Ok(())
     }
 
-    fn toggle(&mut self) -> Result<()> {
-        Ok(self.messages.send(Message::Toggle)?)
+    /// Passes on tempo changes from elsewhere, and the start of each beat, eg. from a MIDI clock,
+    /// which the looper keeps in step with.
+    fn sync_tempo(&mut self) -> Result<()> {
+        if let Some(tempo) = self.tempo.changed(&mut self.last_tempo)? {
+            let frames_per_beat = tempo.beat_duration_in_frames(self.sample_rate);
+            self.messages.send(Message::SetBeat(frames_per_beat))?;
+        }
+
+        if let Some(tempo) = self.tempo.get()? {
+            if self.last_beat_start != Some(tempo.start_timestamp()) {
+                self.last_beat_start = Some(tempo.start_timestamp());
+                self.messages.send(Message::SyncBeat)?;
+            }
+        }
+
+        Ok(())
     }
 
-    fn enable_overdub_mode(&mut self) -> Result<()> {
-        Ok(self.messages.send(Message::QueueOverdub)?)
+    fn track_message(
+        track: &LooperTrackConfig,
+        index: TrackIndex,
+        is_note: impl Fn(Option<NoteOn>) -> bool,
+    ) -> Option<Message> {
+        let commands = &track.commands;
+
+        vec![
+            (Some(track.toggle), Message::Toggle(index)),
+            (track.overdub, Message::QueueOverdub(index)),
+            (track.mute, Message::ToggleMute(index)),
+            (track.undo, Message::Undo(index)),
+            (track.redo, Message::Redo(index)),
+            (commands.stop, Message::Stop(index)),
+            (commands.play, Message::Play(index)),
+            (commands.one_shot, Message::OneShot(index)),
+            (commands.clear, Message::Clear(index)),
+            (commands.multiply, Message::Multiply(index)),
+            (commands.half_speed, Message::ToggleHalfSpeed(index)),
+            (commands.double_speed, Message::ToggleDoubleSpeed(index)),
+        ]
+        .into_iter()
+        .find(|(note, _)| is_note(*note))
+        .map(|(_, message)| message)
     }
 }
 
@@ -67,6 +264,12 @@ impl Effect for Looper {
         output: &mut [f32],
     ) -> Result<()> {
         self.handle_midi_messages(midi_messages)?;
+        self.handle_events()?;
+        self.sync_tempo()?;
         self.split.process(input, output)
     }
+
+    fn parameters(&self) -> SharedParameters {
+        self.parameters.clone()
+    }
 }
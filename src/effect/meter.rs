@@ -0,0 +1,151 @@
+use crate::{util, Result};
+use anyhow::anyhow;
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// The level of a signal over a period of time.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Level {
+    /// The largest absolute sample value.
+    pub peak: f32,
+    pub rms: f32,
+    /// The number of samples at or above full scale, since the meter was created.
+    pub clips: u64,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "peak {:.1} dB, rms {:.1} dB, {} clips",
+            util::gain_to_db(self.peak),
+            util::gain_to_db(self.rms),
+            self.clips
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+struct Rms {
+    sum_of_squares: f32,
+    samples: usize,
+}
+
+#[derive(Debug, Default)]
+struct Meter {
+    /// The bits of the peak. Levels are never negative, so the bits order the same way as the
+    /// values, and the largest can be kept without a lock.
+    peak: AtomicU32,
+    clips: AtomicU64,
+    rms: Mutex<Rms>,
+}
+
+/// The level of a signal in the pipeline, since it was last read.
+#[derive(Clone, Debug, Default)]
+pub struct SharedMeter(Arc<Meter>);
+
+impl SharedMeter {
+    const CLIP_LEVEL: f32 = 1.0;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a buffer to the level. The peak and clips are always counted, but the RMS skips the
+    /// buffer if it is being read, so that the audio thread never waits.
+    pub fn measure(&self, samples: &[f32]) {
+        let mut peak = 0.0_f32;
+        let mut sum_of_squares = 0.0;
+        let mut clips = 0;
+
+        for sample in samples {
+            let level = sample.abs();
+
+            peak = peak.max(level);
+            sum_of_squares += sample * sample;
+            if level >= Self::CLIP_LEVEL {
+                clips += 1;
+            }
+        }
+
+        self.0.peak.fetch_max(peak.to_bits(), Ordering::Relaxed);
+        self.0.clips.fetch_add(clips, Ordering::Relaxed);
+
+        if let Ok(mut rms) = self.0.rms.try_lock() {
+            rms.sum_of_squares += sum_of_squares;
+            rms.samples += samples.len();
+        }
+    }
+
+    /// The level since the last time it was taken. Clips keep being counted.
+    pub fn take(&self) -> Result<Level> {
+        let mut rms = self
+            .0
+            .rms
+            .lock()
+            .map_err(|_| anyhow!("meter lock was poisoned"))?;
+
+        let level = Level {
+            peak: f32::from_bits(self.0.peak.swap(0, Ordering::Relaxed)),
+            rms: if rms.samples > 0 {
+                (rms.sum_of_squares / rms.samples as f32).sqrt()
+            } else {
+                0.0
+            },
+            clips: self.0.clips.load(Ordering::Relaxed),
+        };
+
+        *rms = Rms::default();
+
+        Ok(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_is_reset_when_taken() {
+        let meter = SharedMeter::new();
+
+        meter.measure(&[0.25, -0.5]);
+        meter.measure(&[0.5, -0.25]);
+        let level = meter.take().unwrap();
+        assert_eq!(level.peak, 0.5);
+        assert!((level.rms - 0.15625_f32.sqrt()).abs() < 1e-6);
+
+        assert_eq!(meter.take().unwrap(), Level::default());
+    }
+
+    #[test]
+    fn test_clips_keep_being_counted() {
+        let meter = SharedMeter::new();
+
+        meter.measure(&[1.0, 0.5, -1.5]);
+        assert_eq!(meter.take().unwrap().clips, 2);
+
+        meter.measure(&[1.2]);
+        assert_eq!(meter.take().unwrap().clips, 3);
+    }
+
+    #[test]
+    fn test_peak_and_clips_are_measured_while_being_read() {
+        let meter = SharedMeter::new();
+
+        {
+            let _rms = meter.0.rms.lock().unwrap();
+            meter.measure(&[0.5, -1.5]);
+        }
+
+        let level = meter.take().unwrap();
+        assert_eq!(level.peak, 1.5);
+        assert_eq!(level.clips, 1);
+        assert_eq!(level.rms, 0.0);
+    }
+}
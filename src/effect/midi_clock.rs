@@ -0,0 +1,80 @@
+use crate::{audio::midi::Message, effect::Tempo};
+use wmidi::MidiMessage;
+
+/// Follows the tempo of a MIDI clock, which sends 24 pulses per beat.
+#[derive(Debug, Default)]
+pub struct MidiClock {
+    /// The timestamp of the first pulse of the current beat.
+    beat_start: Option<u64>,
+    pulses: u32,
+}
+
+impl MidiClock {
+    const PULSES_PER_BEAT: u32 = 24;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the tempo at the end of the latest beat in `messages`, if any ended.
+    pub fn handle_messages(&mut self, messages: &[Message]) -> Option<Tempo> {
+        messages
+            .iter()
+            .flat_map(|message| self.handle_message(message))
+            .last()
+    }
+
+    fn handle_message(&mut self, message: &Message) -> Option<Tempo> {
+        match message.message {
+            MidiMessage::TimingClock => self.handle_pulse(message.timestamp),
+            // the clock restarts on the next pulse
+            MidiMessage::Start | MidiMessage::Stop => {
+                self.beat_start = None;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn handle_pulse(&mut self, timestamp: u64) -> Option<Tempo> {
+        let beat_start = match self.beat_start {
+            Some(beat_start) => beat_start,
+            None => {
+                self.beat_start = Some(timestamp);
+                self.pulses = 0;
+                return None;
+            }
+        };
+
+        self.pulses += 1;
+
+        if self.pulses == Self::PULSES_PER_BEAT {
+            self.beat_start = Some(timestamp);
+            self.pulses = 0;
+            Some(Tempo::new(timestamp, timestamp - beat_start))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handle_messages() {
+        let mut midi_clock = MidiClock::new();
+
+        // 120 bpm, so a pulse every 500ms / 24
+        let pulses: Vec<_> = (0..=48)
+            .map(|n| Message::new(n * 500_000 / 24, MidiMessage::TimingClock))
+            .collect();
+
+        assert_eq!(midi_clock.handle_messages(&pulses[..24]), None);
+        assert_eq!(
+            midi_clock.handle_messages(&pulses[24..]),
+            Some(Tempo::new(1_000_000, 500_000))
+        );
+    }
+}
@@ -1,17 +1,62 @@
+mod bitcrusher;
+mod bypass;
+mod compressor;
 mod delay;
+mod drive;
+mod eq;
 mod fft;
+mod freeze;
+mod gate;
+mod granular;
+mod limiter;
 mod looper;
+mod meter;
+mod midi_clock;
+mod modulation;
+mod parameters;
+mod phaser;
 mod pipeline;
+mod pitch_shift;
+mod ring_modulator;
+mod sample_rate_reducer;
+mod shared_tempo;
+mod sidechain;
+mod statuses;
 mod tap_tempo;
 mod tempo;
 mod transparent;
+mod tremolo;
+mod tuner;
+mod wah;
 
+pub use bitcrusher::Bitcrusher;
+pub use bypass::Bypass;
+pub use compressor::Compressor;
 pub use delay::Delay;
+pub use drive::Drive;
+pub use eq::Eq;
 pub use fft::Fft;
+pub use freeze::Freeze;
+pub use gate::Gate;
+pub use granular::Granular;
+pub use limiter::Limiter;
 pub use looper::Looper;
+pub use meter::{Level, SharedMeter};
+pub use modulation::Modulation;
+pub use parameters::{Parameter, SharedParameters};
+pub use phaser::Phaser;
 pub use pipeline::Pipeline;
+pub use pitch_shift::PitchShift;
+pub use ring_modulator::RingModulator;
+pub use sample_rate_reducer::SampleRateReducer;
+pub use shared_tempo::SharedTempo;
+pub use sidechain::{Sidechain, Sidechains};
+pub use statuses::Statuses;
 pub use tempo::Tempo;
 pub use transparent::Transparent;
+pub use tremolo::{Kind as TremoloKind, Tremolo};
+pub use tuner::Tuner;
+pub use wah::Wah;
 
 use crate::{audio::midi::Message, config, Result};
 use cpal::StreamConfig;
@@ -26,6 +71,11 @@ pub trait Effect: Send {
         output: &mut [f32],
     ) -> Result<()>;
 
+    /// The parameters which can change while the effect runs, for displaying.
+    fn parameters(&self) -> SharedParameters {
+        SharedParameters::default()
+    }
+
     fn boxed(self) -> Boxed
     where
         Self: 'static + Sized,
@@ -34,11 +84,81 @@ pub trait Effect: Send {
     }
 }
 
-pub fn from(config: config::Effect, stream_config: &StreamConfig) -> Result<Boxed> {
+pub fn from(
+    config: config::Effect,
+    stream_config: &StreamConfig,
+    sidechains: &mut Sidechains,
+    tempo: &SharedTempo,
+    statuses: &mut Statuses,
+) -> Result<Boxed> {
     Ok(match config {
         config::Effect::Transparent => Transparent::new().boxed(),
-        config::Effect::Delay(delay_config) => Delay::new(delay_config, stream_config)?.boxed(),
-        config::Effect::Looper(looper_config) => Looper::new(looper_config, stream_config)?.boxed(),
+        config::Effect::Delay(delay_config) => {
+            Delay::new(delay_config, stream_config, tempo)?.boxed()
+        }
+        config::Effect::Drive(drive_config) => Drive::new(drive_config, stream_config)?.boxed(),
+        config::Effect::Eq(eq_config) => Eq::new(eq_config, stream_config)?.boxed(),
+        config::Effect::Looper(looper_config) => {
+            Looper::new(looper_config, stream_config, tempo, statuses)?.boxed()
+        }
         config::Effect::Fft => Fft::new().boxed(),
+        config::Effect::Compressor(compressor_config) => {
+            Compressor::new(compressor_config, stream_config, sidechains)?.boxed()
+        }
+        config::Effect::Limiter(limiter_config) => {
+            Limiter::new(limiter_config, stream_config)?.boxed()
+        }
+        config::Effect::Gate(gate_config) => {
+            Gate::new(gate_config, stream_config, sidechains)?.boxed()
+        }
+        config::Effect::Sidechain(sidechain_config) => {
+            Sidechain::new(sidechain_config, sidechains)?.boxed()
+        }
+        config::Effect::Chorus(modulation_config) => Modulation::new(
+            config::ModulationKind::Chorus,
+            modulation_config,
+            stream_config,
+        )?
+        .boxed(),
+        config::Effect::Flanger(modulation_config) => Modulation::new(
+            config::ModulationKind::Flanger,
+            modulation_config,
+            stream_config,
+        )?
+        .boxed(),
+        config::Effect::Vibrato(modulation_config) => Modulation::new(
+            config::ModulationKind::Vibrato,
+            modulation_config,
+            stream_config,
+        )?
+        .boxed(),
+        config::Effect::Tremolo(tremolo_config) => {
+            Tremolo::new(TremoloKind::Tremolo, tremolo_config, stream_config)?.boxed()
+        }
+        config::Effect::HarmonicTremolo(tremolo_config) => {
+            Tremolo::new(TremoloKind::HarmonicTremolo, tremolo_config, stream_config)?.boxed()
+        }
+        config::Effect::AutoPan(tremolo_config) => {
+            Tremolo::new(TremoloKind::AutoPan, tremolo_config, stream_config)?.boxed()
+        }
+        config::Effect::Phaser(phaser_config) => Phaser::new(phaser_config, stream_config)?.boxed(),
+        config::Effect::PitchShift(pitch_shift_config) => {
+            PitchShift::new(pitch_shift_config, stream_config)?.boxed()
+        }
+        config::Effect::Freeze(freeze_config) => Freeze::new(freeze_config, stream_config)?.boxed(),
+        config::Effect::Wah(wah_config) => Wah::new(wah_config, stream_config)?.boxed(),
+        config::Effect::Bitcrusher(bitcrusher_config) => {
+            Bitcrusher::new(bitcrusher_config)?.boxed()
+        }
+        config::Effect::SampleRateReducer(sample_rate_reducer_config) => {
+            SampleRateReducer::new(sample_rate_reducer_config, stream_config)?.boxed()
+        }
+        config::Effect::RingModulator(ring_modulator_config) => {
+            RingModulator::new(ring_modulator_config, stream_config)?.boxed()
+        }
+        config::Effect::Tuner(tuner_config) => Tuner::new(tuner_config, stream_config)?.boxed(),
+        config::Effect::Granular(granular_config) => {
+            Granular::new(granular_config, stream_config)?.boxed()
+        }
     })
 }
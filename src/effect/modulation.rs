@@ -0,0 +1,134 @@
+use super::tap_tempo::TapTempo;
+use crate::{
+    audio::midi,
+    audio_unit::{self, modulated_delay, AudioUnit},
+    config::{ModulationConfig, ModulationKind},
+    effect::{Effect, SharedParameters},
+    Result,
+};
+use anyhow::anyhow;
+use cpal::StreamConfig;
+use std::sync::mpsc::Sender;
+
+pub struct Modulation {
+    config: ModulationConfig,
+    depth_ms: f32,
+    tap_tempo: Option<TapTempo>,
+    unit: audio_unit::ModulatedDelay,
+    messages: Sender<modulated_delay::Message>,
+    parameters: SharedParameters,
+}
+
+impl Modulation {
+    const MAX_FEEDBACK: f32 = 0.95;
+
+    pub fn new(
+        kind: ModulationKind,
+        config: ModulationConfig,
+        stream_config: &StreamConfig,
+    ) -> Result<Self> {
+        let parameters = config.parameters(kind);
+
+        Self::validate_config(&config, &parameters)?;
+
+        let tap_tempo = config.tap_tempo.map(TapTempo::new);
+
+        // leave room for the depth slider, which can double the depth
+        let max_delay_ms = parameters.delay_ms + parameters.depth_ms * 2.0;
+        let (unit, messages) =
+            audio_unit::ModulatedDelay::new(stream_config, parameters, max_delay_ms);
+
+        Ok(Self {
+            config,
+            depth_ms: parameters.depth_ms,
+            tap_tempo,
+            unit,
+            messages,
+            parameters: SharedParameters::new(&[
+                ("rate_hz", parameters.rate_hz),
+                ("depth_ms", parameters.depth_ms),
+                ("feedback", parameters.feedback),
+            ]),
+        })
+    }
+
+    fn validate_config(
+        config: &ModulationConfig,
+        parameters: &modulated_delay::Parameters,
+    ) -> Result<()> {
+        if parameters.delay_ms >= 0.0
+            && parameters.depth_ms >= 0.0
+            && parameters.feedback.abs() <= Self::MAX_FEEDBACK
+            && (0.0..=1.0).contains(&parameters.mix)
+            && config.min_rate_hz > 0.0
+            && config.min_rate_hz <= config.max_rate_hz
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid modulation config: {:#?}", config))
+        }
+    }
+
+    fn handle_midi_messages(&mut self, messages: &[midi::Message]) -> Result<()> {
+        if let Some(rate_hz) = self.config.rate_slider.and_then(|slider| {
+            midi::latest_slider_value_exponential(
+                slider,
+                self.config.min_rate_hz,
+                self.config.max_rate_hz,
+                messages,
+            )
+        }) {
+            self.messages
+                .send(modulated_delay::Message::SetRate(rate_hz))?;
+            self.parameters.set("rate_hz", rate_hz);
+        }
+
+        if let Some(tempo) = self
+            .tap_tempo
+            .as_mut()
+            .and_then(|tap_tempo| tap_tempo.handle_messages(messages))
+        {
+            let rate_hz = tempo.subdivision_frequency(self.config.subdivision);
+            self.messages
+                .send(modulated_delay::Message::SetRate(rate_hz))?;
+            self.parameters.set("rate_hz", rate_hz);
+            self.messages.send(modulated_delay::Message::ResetPhase)?;
+        }
+
+        if let Some(depth_ms) = self.config.depth_slider.and_then(|slider| {
+            midi::latest_slider_value(slider, 0.0, self.depth_ms * 2.0, messages)
+        }) {
+            self.messages
+                .send(modulated_delay::Message::SetDepth(depth_ms))?;
+            self.parameters.set("depth_ms", depth_ms);
+        }
+
+        if let Some(feedback) = self
+            .config
+            .feedback_slider
+            .and_then(|slider| midi::latest_slider_value(slider, 0.0, Self::MAX_FEEDBACK, messages))
+        {
+            self.messages
+                .send(modulated_delay::Message::SetFeedback(feedback))?;
+            self.parameters.set("feedback", feedback);
+        }
+
+        Ok(())
+    }
+}
+
+impl Effect for Modulation {
+    fn process(
+        &mut self,
+        midi_messages: &[midi::Message],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        self.handle_midi_messages(midi_messages)?;
+        self.unit.process(input, output)
+    }
+
+    fn parameters(&self) -> SharedParameters {
+        self.parameters.clone()
+    }
+}
@@ -0,0 +1,76 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+/// One of an effect's parameters, which can be changed from the audio thread without locking.
+#[derive(Clone, Debug)]
+pub struct Parameter(Arc<AtomicU32>);
+
+impl Parameter {
+    /// Holds the bits of the value, since there is no atomic float.
+    fn new(value: f32) -> Self {
+        Self(Arc::new(AtomicU32::new(value.to_bits())))
+    }
+
+    pub fn set(&self, value: f32) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// The values of an effect's parameters as they change while the pipeline runs, eg. when a slider
+/// is moved, named the same as in the config.
+#[derive(Clone, Debug, Default)]
+pub struct SharedParameters(Vec<(String, Parameter)>);
+
+impl SharedParameters {
+    pub fn new(parameters: &[(&str, f32)]) -> Self {
+        let mut shared = Self::default();
+
+        for (name, value) in parameters {
+            shared.add(*name, *value);
+        }
+
+        shared
+    }
+
+    /// Adds a parameter, and returns it so that it can be set without looking it up by name.
+    pub fn add(&mut self, name: impl Into<String>, value: f32) -> Parameter {
+        let parameter = Parameter::new(value);
+        self.0.push((name.into(), parameter.clone()));
+        parameter
+    }
+
+    /// Does nothing if there is no parameter called `name`.
+    pub fn set(&self, name: &str, value: f32) {
+        if let Some((_, parameter)) = self.0.iter().find(|(existing, _)| existing == name) {
+            parameter.set(value);
+        }
+    }
+
+    pub fn get(&self) -> Vec<(&str, f32)> {
+        self.0
+            .iter()
+            .map(|(name, parameter)| (name.as_str(), parameter.get()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_is_seen_by_clones() {
+        let parameters = SharedParameters::new(&[("delay_ms", 300.0), ("mix", 0.5)]);
+        let shared = parameters.clone();
+
+        parameters.set("mix", 0.25);
+        parameters.set("missing", 1.0);
+        assert_eq!(shared.get(), vec![("delay_ms", 300.0), ("mix", 0.25)]);
+    }
+}
@@ -0,0 +1,139 @@
+use super::tap_tempo::TapTempo;
+use crate::{
+    audio::midi,
+    audio_unit::{self, phaser, AudioUnit},
+    config::PhaserConfig,
+    effect::{Effect, SharedParameters},
+    Result,
+};
+use anyhow::anyhow;
+use cpal::StreamConfig;
+use std::sync::mpsc::Sender;
+
+pub struct Phaser {
+    config: PhaserConfig,
+    tap_tempo: Option<TapTempo>,
+    unit: audio_unit::Phaser,
+    messages: Sender<phaser::Message>,
+    parameters: SharedParameters,
+}
+
+impl Phaser {
+    const VALID_STAGES: [usize; 4] = [4, 6, 8, 12];
+    const MAX_FEEDBACK: f32 = 0.95;
+
+    pub fn new(config: PhaserConfig, stream_config: &StreamConfig) -> Result<Self> {
+        Self::validate_config(&config)?;
+
+        let parameters = phaser::Parameters {
+            shape: config.shape,
+            rate_hz: config.rate_hz,
+            depth: config.depth,
+            feedback: config.feedback,
+            center_hz: config.center_hz,
+            mix: config.mix,
+        };
+        let (unit, messages) = audio_unit::Phaser::new(stream_config, parameters, config.stages);
+
+        let parameters = SharedParameters::new(&[
+            ("rate_hz", config.rate_hz),
+            ("depth", config.depth),
+            ("feedback", config.feedback),
+            ("center_hz", config.center_hz),
+        ]);
+
+        Ok(Self {
+            config,
+            tap_tempo: config.tap_tempo.map(TapTempo::new),
+            unit,
+            messages,
+            parameters,
+        })
+    }
+
+    fn validate_config(config: &PhaserConfig) -> Result<()> {
+        if Self::VALID_STAGES.contains(&config.stages)
+            && config.feedback.abs() <= Self::MAX_FEEDBACK
+            && (0.0..=1.0).contains(&config.mix)
+            && config.min_rate_hz > 0.0
+            && config.min_rate_hz <= config.max_rate_hz
+            && config.min_center_hz > 0.0
+            && config.min_center_hz <= config.max_center_hz
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid phaser config: {:#?}", config))
+        }
+    }
+
+    fn handle_midi_messages(&mut self, messages: &[midi::Message]) -> Result<()> {
+        if let Some(rate_hz) = self.config.rate_slider.and_then(|slider| {
+            midi::latest_slider_value_exponential(
+                slider,
+                self.config.min_rate_hz,
+                self.config.max_rate_hz,
+                messages,
+            )
+        }) {
+            self.messages.send(phaser::Message::SetRate(rate_hz))?;
+            self.parameters.set("rate_hz", rate_hz);
+        }
+
+        if let Some(tempo) = self
+            .tap_tempo
+            .as_mut()
+            .and_then(|tap_tempo| tap_tempo.handle_messages(messages))
+        {
+            let rate_hz = tempo.subdivision_frequency(self.config.subdivision);
+            self.messages.send(phaser::Message::SetRate(rate_hz))?;
+            self.parameters.set("rate_hz", rate_hz);
+            self.messages.send(phaser::Message::ResetPhase)?;
+        }
+
+        if let Some(depth) = self.config.depth_slider.and_then(|slider| {
+            midi::latest_slider_value(slider, 0.0, self.config.max_depth, messages)
+        }) {
+            self.messages.send(phaser::Message::SetDepth(depth))?;
+            self.parameters.set("depth", depth);
+        }
+
+        if let Some(feedback) = self
+            .config
+            .feedback_slider
+            .and_then(|slider| midi::latest_slider_value(slider, 0.0, Self::MAX_FEEDBACK, messages))
+        {
+            self.messages.send(phaser::Message::SetFeedback(feedback))?;
+            self.parameters.set("feedback", feedback);
+        }
+
+        if let Some(center_hz) = self.config.center_slider.and_then(|slider| {
+            midi::latest_slider_value_exponential(
+                slider,
+                self.config.min_center_hz,
+                self.config.max_center_hz,
+                messages,
+            )
+        }) {
+            self.messages.send(phaser::Message::SetCenter(center_hz))?;
+            self.parameters.set("center_hz", center_hz);
+        }
+
+        Ok(())
+    }
+}
+
+impl Effect for Phaser {
+    fn process(
+        &mut self,
+        midi_messages: &[midi::Message],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        self.handle_midi_messages(midi_messages)?;
+        self.unit.process(input, output)
+    }
+
+    fn parameters(&self) -> SharedParameters {
+        self.parameters.clone()
+    }
+}
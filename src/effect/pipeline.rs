@@ -1,28 +1,107 @@
 use cpal::StreamConfig;
 
+use super::{midi_clock::MidiClock, tap_tempo::TapTempo};
 use crate::{
     audio::midi::Message,
-    effect::{self, Effect},
+    effect::{self, Bypass, Effect, SharedMeter, SharedTempo, Sidechains, Statuses},
     Config, Result,
 };
 
 pub struct Pipeline {
     effects: Vec<effect::Boxed>,
+    bypass: Bypass,
+    tempo: SharedTempo,
+    tap_tempo: Option<TapTempo>,
+    midi_clock: Option<MidiClock>,
+    statuses: Statuses,
 }
 
 impl Pipeline {
     pub fn from(config: &Config, stream_config: &StreamConfig) -> Result<Self> {
+        let mut sidechains = Sidechains::new();
+        let tempo = SharedTempo::new();
+        let mut statuses = Statuses {
+            tempo: tempo.clone(),
+            ..Statuses::new()
+        };
+
+        if config.meters.between_effects {
+            statuses.effects = config
+                .effects
+                .iter()
+                .map(|effect_config| (effect_config.name(), SharedMeter::new()))
+                .collect();
+        }
+
         let effects = config
             .effects
             .iter()
-            .map(|effect_config| effect::from(*effect_config, stream_config))
+            .map(|effect_config| {
+                effect::from(
+                    effect_config.clone(),
+                    stream_config,
+                    &mut sidechains,
+                    &tempo,
+                    &mut statuses,
+                )
+            })
             .collect::<Result<Vec<_>>>()?;
 
-        Ok(Self { effects })
+        sidechains.validate()?;
+        statuses.parameters = effects.iter().map(|effect| effect.parameters()).collect();
+
+        Ok(Self {
+            bypass: Bypass::new(effects.len()),
+            effects,
+            tempo,
+            tap_tempo: config.tempo.tap_tempo.map(TapTempo::new),
+            midi_clock: config.tempo.midi_clock.then(MidiClock::new),
+            statuses,
+        })
     }
 
     pub fn new(effects: Vec<effect::Boxed>) -> Self {
-        Self { effects }
+        let tempo = SharedTempo::new();
+
+        Self {
+            bypass: Bypass::new(effects.len()),
+            effects,
+            tempo: tempo.clone(),
+            tap_tempo: None,
+            midi_clock: None,
+            statuses: Statuses {
+                tempo,
+                ..Statuses::new()
+            },
+        }
+    }
+
+    /// The live state of the effects, for displaying while the pipeline runs on the audio thread.
+    pub fn statuses(&self) -> Statuses {
+        self.statuses.clone()
+    }
+
+    /// Which effects are bypassed, which can be changed while the pipeline runs.
+    pub fn bypass(&self) -> Bypass {
+        self.bypass.clone()
+    }
+
+    /// Updates the shared tempo, before any effects try to follow it.
+    fn handle_midi_messages(&mut self, messages: &[Message]) -> Result<()> {
+        let tapped = self
+            .tap_tempo
+            .as_mut()
+            .and_then(|tap_tempo| tap_tempo.handle_messages(messages));
+        let clocked = self
+            .midi_clock
+            .as_mut()
+            .and_then(|midi_clock| midi_clock.handle_messages(messages));
+
+        if let Some(tempo) = tapped.or(clocked) {
+            self.tempo.set(tempo)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -33,13 +112,100 @@ impl Effect for Pipeline {
         input: &[f32],
         output: &mut [f32],
     ) -> Result<()> {
+        self.handle_midi_messages(midi_messages)?;
+        self.statuses.input.measure(input);
+
         let mut input = input.to_vec();
 
-        for effect in &mut self.effects {
-            effect.process(midi_messages, &input, output)?;
-            input.copy_from_slice(output);
+        for (index, effect) in self.effects.iter_mut().enumerate() {
+            if self.bypass.is_bypassed(index) {
+                output.copy_from_slice(&input);
+            } else {
+                effect.process(midi_messages, &input, output)?;
+                input.copy_from_slice(output);
+            }
+
+            if let Some((_, meter)) = self.statuses.effects.get(index) {
+                meter.measure(output);
+            }
         }
 
+        self.statuses.output.measure(output);
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_unit::looper::State;
+    use cpal::{BufferSize, SampleRate};
+    use wmidi::{Channel, MidiMessage, Note, U7};
+
+    #[test]
+    fn test_midi_clock_keeps_the_bar() {
+        let config = Config::from(
+            "
+tempo:
+  midi_clock: true
+effects:
+  - type: Looper
+    quantize: Bar
+    toggle:
+      channel: 1
+      note: 60
+",
+        )
+        .unwrap();
+        let stream_config = StreamConfig {
+            channels: 1,
+            sample_rate: SampleRate(1_000),
+            buffer_size: BufferSize::Default,
+        };
+        let mut pipeline = Pipeline::from(&config, &stream_config).unwrap();
+        let status = pipeline.statuses().loopers[0].clone();
+
+        // 120 bpm, so a beat is 500 frames, and a bar of 4 beats is 2000. The first beat is
+        // measured 2ms long, and the next 2ms short, which leaves the grid's beat 2 frames short,
+        // since later beats are within the tolerance of it
+        let pulse = |n: u64| {
+            let jitter = if n == 24 { 2_000 } else { 0 };
+            Message::new(n * 500_000 / 24 + jitter, MidiMessage::TimingClock)
+        };
+        let pulses = |beat: u64| {
+            let first = if beat == 0 { 0 } else { beat * 24 + 1 };
+            (first..=(beat + 1) * 24).map(pulse).collect::<Vec<_>>()
+        };
+        let mut output = vec![0.0; 500];
+
+        // each buffer starts with a beat, and the first one starts the bar. After 16 bars, a grid
+        // which only counted frames would be 128 frames behind
+        for beat in 0..65 {
+            pipeline
+                .process(&pulses(beat), &[0.0; 500], &mut output)
+                .unwrap();
+        }
+
+        // the toggle arrives on the second beat of a bar
+        let mut messages = pulses(65);
+        messages.push(Message::new(
+            33_000_000,
+            MidiMessage::NoteOn(Channel::Ch1, Note::C4, U7::MAX),
+        ));
+        pipeline
+            .process(&messages, &[0.0; 500], &mut output)
+            .unwrap();
+        for beat in 66..68 {
+            pipeline
+                .process(&pulses(beat), &[0.0; 500], &mut output)
+                .unwrap();
+        }
+
+        // recording started within a couple of frames of the next bar, at the end of the buffer,
+        // rather than a beat later or when the drifting grid reached its bar
+        let track = status.get().unwrap().tracks[0];
+        assert_eq!(track.state, State::Recording);
+        assert!(track.position <= 2, "recorded {} frames", track.position);
+    }
+}
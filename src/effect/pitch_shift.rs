@@ -0,0 +1,130 @@
+use crate::{
+    audio::midi,
+    audio_unit::{self, AudioUnit},
+    config::{PitchShiftConfig, PitchShiftVoiceConfig},
+    effect::Effect,
+    util::log,
+    Result,
+};
+use anyhow::anyhow;
+use cpal::StreamConfig;
+use wmidi::MidiMessage;
+
+struct Voice {
+    config: PitchShiftVoiceConfig,
+    unit: audio_unit::PitchShifter,
+    enabled: bool,
+    // the gain at the end of the last buffer, so that toggling the voice ramps instead of clicks
+    gain: f32,
+}
+
+impl Voice {
+    fn new(config: PitchShiftVoiceConfig, stream_config: &StreamConfig, window_ms: f32) -> Self {
+        let unit = audio_unit::PitchShifter::new(stream_config, config.interval(), window_ms);
+
+        Self {
+            config,
+            unit,
+            enabled: config.enabled,
+            gain: if config.enabled { config.level } else { 0.0 },
+        }
+    }
+
+    fn handle_midi_messages(&mut self, messages: &[midi::Message]) {
+        let toggle = match self.config.toggle {
+            Some(toggle) => toggle,
+            None => return,
+        };
+
+        for message in messages {
+            match message.message {
+                MidiMessage::NoteOn(channel, note, _)
+                    if channel == toggle.channel && note == toggle.note =>
+                {
+                    self.enabled = !self.enabled;
+                    log::print(format!(
+                        "pitch shift: {} semitone voice {}",
+                        self.config.interval(),
+                        if self.enabled { "on" } else { "off" }
+                    ));
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Adds the voice's output into `output`, scaled by `mix`.
+    fn process(
+        &mut self,
+        input: &[f32],
+        voice_output: &mut [f32],
+        output: &mut [f32],
+        mix: f32,
+    ) -> Result<()> {
+        self.unit.process(input, voice_output)?;
+
+        let target = if self.enabled { self.config.level } else { 0.0 };
+        let step = (target - self.gain) / output.len() as f32;
+
+        for (i, sample) in voice_output.iter().enumerate() {
+            output[i] += sample * (self.gain + step * i as f32) * mix;
+        }
+
+        self.gain = target;
+
+        Ok(())
+    }
+}
+
+pub struct PitchShift {
+    mix: f32,
+    voices: Vec<Voice>,
+}
+
+impl PitchShift {
+    pub fn new(config: PitchShiftConfig, stream_config: &StreamConfig) -> Result<Self> {
+        Self::validate_config(&config)?;
+
+        let voices = config
+            .voices
+            .iter()
+            .map(|voice_config| Voice::new(*voice_config, stream_config, config.window_ms))
+            .collect();
+
+        Ok(Self {
+            mix: config.mix,
+            voices,
+        })
+    }
+
+    fn validate_config(config: &PitchShiftConfig) -> Result<()> {
+        if !config.voices.is_empty() && (0.0..=1.0).contains(&config.mix) && config.window_ms > 0.0
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid pitch shift config: {:#?}", config))
+        }
+    }
+}
+
+impl Effect for PitchShift {
+    fn process(
+        &mut self,
+        midi_messages: &[midi::Message],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        for (i, sample) in input.iter().enumerate() {
+            output[i] = sample * (1.0 - self.mix);
+        }
+
+        let mut voice_output = vec![0.0; output.len()];
+
+        for voice in &mut self.voices {
+            voice.handle_midi_messages(midi_messages);
+            voice.process(input, &mut voice_output, output, self.mix)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{
+    audio::midi,
+    audio_unit::{self, ring_modulator, AudioUnit},
+    config::RingModulatorConfig,
+    effect::{Effect, SharedParameters},
+    util::log,
+    Result,
+};
+use anyhow::anyhow;
+use cpal::StreamConfig;
+use std::sync::mpsc::Sender;
+use wmidi::MidiMessage;
+
+pub struct RingModulator {
+    config: RingModulatorConfig,
+    unit: audio_unit::RingModulator,
+    messages: Sender<ring_modulator::Message>,
+    parameters: SharedParameters,
+}
+
+impl RingModulator {
+    pub fn new(config: RingModulatorConfig, stream_config: &StreamConfig) -> Result<Self> {
+        Self::validate_config(&config)?;
+
+        let (unit, messages) = audio_unit::RingModulator::new(
+            stream_config,
+            config.shape,
+            config.frequency_hz,
+            config.mix,
+        );
+
+        let parameters =
+            SharedParameters::new(&[("frequency_hz", config.frequency_hz), ("mix", config.mix)]);
+
+        Ok(Self {
+            config,
+            unit,
+            messages,
+            parameters,
+        })
+    }
+
+    fn validate_config(config: &RingModulatorConfig) -> Result<()> {
+        if config.min_frequency_hz > 0.0
+            && config.min_frequency_hz <= config.max_frequency_hz
+            && config.frequency_hz > 0.0
+            && (0.0..=1.0).contains(&config.mix)
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid ring modulator config: {:#?}", config))
+        }
+    }
+
+    fn handle_midi_messages(&mut self, messages: &[midi::Message]) -> Result<()> {
+        if let Some(frequency) = self.config.frequency_slider.and_then(|slider| {
+            midi::latest_slider_value_exponential(
+                slider,
+                self.config.min_frequency_hz,
+                self.config.max_frequency_hz,
+                messages,
+            )
+        }) {
+            self.messages
+                .send(ring_modulator::Message::SetFrequency(frequency))?;
+            self.parameters.set("frequency_hz", frequency);
+        }
+
+        if let Some(mix) = self
+            .config
+            .mix_slider
+            .and_then(|slider| midi::latest_slider_value(slider, 0.0, 1.0, messages))
+        {
+            self.messages.send(ring_modulator::Message::SetMix(mix))?;
+            self.parameters.set("mix", mix);
+        }
+
+        if let Some(notes) = self.config.notes {
+            let latest_note = messages
+                .iter()
+                .rev()
+                .find_map(|message| match message.message {
+                    MidiMessage::NoteOn(channel, note, _) if channel == notes.channel => Some(note),
+                    _ => None,
+                });
+
+            if let Some(note) = latest_note {
+                log::print(format!("ring modulator: {}", note));
+                self.messages
+                    .send(ring_modulator::Message::SetFrequency(note.to_freq_f32()))?;
+                self.parameters.set("frequency_hz", note.to_freq_f32());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Effect for RingModulator {
+    fn process(
+        &mut self,
+        midi_messages: &[midi::Message],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        self.handle_midi_messages(midi_messages)?;
+        self.unit.process(input, output)
+    }
+
+    fn parameters(&self) -> SharedParameters {
+        self.parameters.clone()
+    }
+}
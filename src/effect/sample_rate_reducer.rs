@@ -0,0 +1,78 @@
+use crate::{
+    audio::midi,
+    audio_unit::{self, sample_rate_reducer, AudioUnit},
+    config::SampleRateReducerConfig,
+    effect::{Effect, SharedParameters},
+    Result,
+};
+use anyhow::anyhow;
+use cpal::StreamConfig;
+use std::sync::mpsc::Sender;
+
+pub struct SampleRateReducer {
+    config: SampleRateReducerConfig,
+    unit: audio_unit::SampleRateReducer,
+    messages: Sender<sample_rate_reducer::Message>,
+    parameters: SharedParameters,
+}
+
+impl SampleRateReducer {
+    pub fn new(config: SampleRateReducerConfig, stream_config: &StreamConfig) -> Result<Self> {
+        Self::validate_config(&config)?;
+
+        let (unit, messages) = audio_unit::SampleRateReducer::new(stream_config, config.rate_hz);
+
+        let parameters = SharedParameters::new(&[("rate_hz", config.rate_hz)]);
+
+        Ok(Self {
+            config,
+            unit,
+            messages,
+            parameters,
+        })
+    }
+
+    fn validate_config(config: &SampleRateReducerConfig) -> Result<()> {
+        if config.min_rate_hz > 0.0
+            && config.min_rate_hz <= config.max_rate_hz
+            && config.rate_hz > 0.0
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid sample rate reducer config: {:#?}", config))
+        }
+    }
+
+    fn handle_midi_messages(&mut self, messages: &[midi::Message]) -> Result<()> {
+        if let Some(rate) = self.config.rate_slider.and_then(|slider| {
+            midi::latest_slider_value_exponential(
+                slider,
+                self.config.min_rate_hz,
+                self.config.max_rate_hz,
+                messages,
+            )
+        }) {
+            self.messages
+                .send(sample_rate_reducer::Message::SetRate(rate))?;
+            self.parameters.set("rate_hz", rate);
+        }
+
+        Ok(())
+    }
+}
+
+impl Effect for SampleRateReducer {
+    fn process(
+        &mut self,
+        midi_messages: &[midi::Message],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        self.handle_midi_messages(midi_messages)?;
+        self.unit.process(input, output)
+    }
+
+    fn parameters(&self) -> SharedParameters {
+        self.parameters.clone()
+    }
+}
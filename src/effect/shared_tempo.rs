@@ -0,0 +1,54 @@
+use crate::{effect::Tempo, Result};
+use anyhow::anyhow;
+use std::sync::{Arc, Mutex};
+
+/// The tempo of the whole pipeline. It is set by tapping, the MIDI clock, or the looper, and any
+/// effect can follow it.
+#[derive(Clone, Debug, Default)]
+pub struct SharedTempo(Arc<Mutex<Option<Tempo>>>);
+
+impl SharedTempo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, tempo: Tempo) -> Result<()> {
+        let mut shared = self
+            .0
+            .lock()
+            .map_err(|_| anyhow!("shared tempo lock was poisoned"))?;
+        *shared = Some(tempo);
+
+        Ok(())
+    }
+
+    pub fn get(&self) -> Result<Option<Tempo>> {
+        let shared = self
+            .0
+            .lock()
+            .map_err(|_| anyhow!("shared tempo lock was poisoned"))?;
+
+        Ok(*shared)
+    }
+
+    /// The tempo, if its beat has changed length since `last` was seen. `last` is updated to the
+    /// current tempo when it has. Beats moving, eg. with every pulse of a MIDI clock, aren't a
+    /// change, so that followers aren't disturbed on every beat.
+    pub fn changed(&self, last: &mut Option<Tempo>) -> Result<Option<Tempo>> {
+        let shared = *self
+            .0
+            .lock()
+            .map_err(|_| anyhow!("shared tempo lock was poisoned"))?;
+
+        let is_same = match (shared, *last) {
+            (Some(shared), Some(last)) => shared.has_same_beat(&last),
+            (shared, last) => shared.is_none() && last.is_none(),
+        };
+        if is_same {
+            return Ok(None);
+        }
+
+        *last = shared;
+        Ok(shared)
+    }
+}
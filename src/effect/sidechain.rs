@@ -0,0 +1,99 @@
+use crate::{audio::midi::Message, config::SidechainConfig, effect::Effect, Result};
+use anyhow::anyhow;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+/// The most recent samples that passed through a `Sidechain` effect.
+#[derive(Clone, Default)]
+pub struct Buffer(Arc<Mutex<Vec<f32>>>);
+
+impl Buffer {
+    fn write(&self, samples: &[f32]) -> Result<()> {
+        let mut buffer = self
+            .0
+            .lock()
+            .map_err(|_| anyhow!("sidechain buffer lock was poisoned"))?;
+        buffer.clear();
+        buffer.extend_from_slice(samples);
+
+        Ok(())
+    }
+
+    /// Passes the sidechain signal to `read`, falling back to `input` when the sidechain hasn't
+    /// produced a buffer of the same length yet (eg. when it comes later in the pipeline and this
+    /// is the first buffer). The signal is borrowed while the lock is held, rather than copied.
+    pub fn read_or<T>(&self, input: &[f32], read: impl FnOnce(&[f32]) -> T) -> Result<T> {
+        let buffer = self
+            .0
+            .lock()
+            .map_err(|_| anyhow!("sidechain buffer lock was poisoned"))?;
+
+        if buffer.len() == input.len() {
+            Ok(read(&buffer))
+        } else {
+            Ok(read(input))
+        }
+    }
+}
+
+/// Connects `Sidechain` effects to the effects which use their signal, by name.
+#[derive(Default)]
+pub struct Sidechains {
+    buffers: HashMap<String, Buffer>,
+    sources: HashSet<String>,
+}
+
+impl Sidechains {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn source(&mut self, name: &str) -> Result<Buffer> {
+        if !self.sources.insert(name.into()) {
+            return Err(anyhow!("Sidechain '{}' is defined more than once", name));
+        }
+
+        Ok(self.buffer(name))
+    }
+
+    pub fn buffer(&mut self, name: &str) -> Buffer {
+        self.buffers.entry(name.into()).or_default().clone()
+    }
+
+    /// Checks that every sidechain which is used has a source in the pipeline.
+    pub fn validate(&self) -> Result<()> {
+        match self
+            .buffers
+            .keys()
+            .find(|name| !self.sources.contains(*name))
+        {
+            Some(name) => Err(anyhow!(
+                "Sidechain '{}' is used, but there is no Sidechain effect with that name",
+                name
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Passes its input through unchanged, while making it available to other effects.
+pub struct Sidechain {
+    buffer: Buffer,
+}
+
+impl Sidechain {
+    pub fn new(config: SidechainConfig, sidechains: &mut Sidechains) -> Result<Self> {
+        Ok(Self {
+            buffer: sidechains.source(&config.name)?,
+        })
+    }
+}
+
+impl Effect for Sidechain {
+    fn process(&mut self, _: &[Message], input: &[f32], output: &mut [f32]) -> Result<()> {
+        output.copy_from_slice(input);
+        self.buffer.write(input)
+    }
+}
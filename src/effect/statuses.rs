@@ -0,0 +1,58 @@
+use crate::{
+    audio_unit::looper::SharedStatus,
+    effect::{SharedMeter, SharedParameters, SharedTempo},
+    util::log,
+    Result,
+};
+use std::{thread, time::Duration};
+
+/// The live state of the effects in a pipeline, which can be read while it runs.
+#[derive(Clone, Debug, Default)]
+pub struct Statuses {
+    pub loopers: Vec<SharedStatus>,
+    pub tempo: SharedTempo,
+    pub input: SharedMeter,
+    pub output: SharedMeter,
+    /// The level after each effect, named by its type. Empty unless metering between effects is
+    /// turned on.
+    pub effects: Vec<(String, SharedMeter)>,
+    /// The parameters of each effect, as they are changed by sliders and the tempo.
+    pub parameters: Vec<SharedParameters>,
+}
+
+impl Statuses {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prints the levels from a separate thread, every `interval`.
+    pub fn print_levels(&self, interval: Duration) {
+        let statuses = self.clone();
+
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+
+            if let Err(error) = statuses.print_levels_once() {
+                log::error(format!("meters: {:?}", error));
+                return;
+            }
+        });
+    }
+
+    fn print_levels_once(&self) -> Result<()> {
+        log::print(format!("meters: input {}", self.input.take()?));
+
+        for (index, (name, meter)) in self.effects.iter().enumerate() {
+            log::print(format!(
+                "meters: after {} {} {}",
+                index + 1,
+                name,
+                meter.take()?
+            ));
+        }
+
+        log::print(format!("meters: output {}", self.output.take()?));
+
+        Ok(())
+    }
+}
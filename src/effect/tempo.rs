@@ -1,3 +1,5 @@
+use crate::config::Subdivision;
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Tempo {
     start_timestamp: u64,
@@ -5,6 +7,9 @@ pub struct Tempo {
 }
 
 impl Tempo {
+    /// How much a beat's length can change, as a fraction of it, before it's a new tempo.
+    const BEAT_TOLERANCE: f32 = 0.005;
+
     pub fn new(start_timestamp: u64, beat_duration: u64) -> Self {
         Self {
             start_timestamp,
@@ -12,7 +17,54 @@ impl Tempo {
         }
     }
 
+    /// When the latest beat started, in microseconds.
+    pub fn start_timestamp(&self) -> u64 {
+        self.start_timestamp
+    }
+
     pub fn beat_duration_as_ms(&self) -> u32 {
         (self.beat_duration / 1000) as u32
     }
+
+    /// A tempo with beats `frames` long, at the given sample rate.
+    pub fn from_frames(frames: usize, sample_rate: u32) -> Self {
+        Self::new(0, frames as u64 * 1_000_000 / sample_rate as u64)
+    }
+
+    /// The length of a beat in frames, at the given sample rate.
+    pub fn beat_duration_in_frames(&self, sample_rate: u32) -> usize {
+        (self.beat_duration * sample_rate as u64 / 1_000_000) as usize
+    }
+
+    /// Whether the beats are close enough in length to be the same tempo, allowing for jitter in
+    /// the timing of taps or clock pulses. Where the beats start is ignored.
+    pub fn has_same_beat(&self, other: &Tempo) -> bool {
+        let difference = self.beat_duration.abs_diff(other.beat_duration);
+        difference as f32 <= self.beat_duration as f32 * Self::BEAT_TOLERANCE
+    }
+
+    /// The number of beats per second.
+    pub fn beat_frequency(&self) -> f32 {
+        1_000_000.0 / self.beat_duration as f32
+    }
+
+    /// The number of times per second that a note of the given length repeats.
+    pub fn subdivision_frequency(&self, subdivision: Subdivision) -> f32 {
+        self.beat_frequency() / subdivision.beats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subdivision_frequency() {
+        // 120 bpm
+        let tempo = Tempo::new(0, 500_000);
+
+        assert_eq!(tempo.subdivision_frequency(Subdivision::Quarter), 2.0);
+        assert_eq!(tempo.subdivision_frequency(Subdivision::Eighth), 4.0);
+        assert!((tempo.subdivision_frequency(Subdivision::DottedEighth) - 8.0 / 3.0).abs() < 1e-5);
+    }
 }
@@ -0,0 +1,128 @@
+use super::tap_tempo::TapTempo;
+use crate::{
+    audio::midi,
+    audio_unit::{self, tremolo, AudioUnit},
+    config::TremoloConfig,
+    effect::{Effect, SharedParameters},
+    util::log,
+    Result,
+};
+use anyhow::anyhow;
+use cpal::StreamConfig;
+use std::sync::mpsc::Sender;
+
+#[derive(Copy, Clone, Debug)]
+pub enum Kind {
+    Tremolo,
+    HarmonicTremolo,
+    AutoPan,
+}
+
+pub struct Tremolo {
+    config: TremoloConfig,
+    tap_tempo: Option<TapTempo>,
+    unit: audio_unit::Tremolo,
+    messages: Sender<tremolo::Message>,
+    parameters: SharedParameters,
+}
+
+impl Tremolo {
+    pub fn new(kind: Kind, config: TremoloConfig, stream_config: &StreamConfig) -> Result<Self> {
+        Self::validate_config(&config)?;
+
+        let unit_kind = match kind {
+            Kind::Tremolo => tremolo::Kind::Amplitude,
+            Kind::HarmonicTremolo => tremolo::Kind::Harmonic {
+                crossover_hz: config.crossover_hz,
+            },
+            Kind::AutoPan => tremolo::Kind::Pan,
+        };
+
+        if let (Kind::AutoPan, 1) = (kind, stream_config.channels) {
+            log::print("auto-pan: the output is mono, so falling back to tremolo");
+        }
+
+        let (unit, messages) = audio_unit::Tremolo::new(
+            stream_config,
+            unit_kind,
+            config.shape,
+            config.rate_hz,
+            config.depth,
+        );
+
+        let parameters =
+            SharedParameters::new(&[("rate_hz", config.rate_hz), ("depth", config.depth)]);
+
+        Ok(Self {
+            config,
+            tap_tempo: config.tap_tempo.map(TapTempo::new),
+            unit,
+            messages,
+            parameters,
+        })
+    }
+
+    fn validate_config(config: &TremoloConfig) -> Result<()> {
+        if (0.0..=1.0).contains(&config.depth)
+            && config.min_rate_hz > 0.0
+            && config.min_rate_hz <= config.max_rate_hz
+            && config.crossover_hz > 0.0
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid tremolo config: {:#?}", config))
+        }
+    }
+
+    fn handle_midi_messages(&mut self, messages: &[midi::Message]) -> Result<()> {
+        if let Some(rate_hz) = self.config.rate_slider.and_then(|slider| {
+            midi::latest_slider_value_exponential(
+                slider,
+                self.config.min_rate_hz,
+                self.config.max_rate_hz,
+                messages,
+            )
+        }) {
+            self.messages.send(tremolo::Message::SetRate(rate_hz))?;
+            self.parameters.set("rate_hz", rate_hz);
+        }
+
+        if let Some(tempo) = self
+            .tap_tempo
+            .as_mut()
+            .and_then(|tap_tempo| tap_tempo.handle_messages(messages))
+        {
+            let rate_hz = tempo.subdivision_frequency(self.config.subdivision);
+            self.messages.send(tremolo::Message::SetRate(rate_hz))?;
+            self.parameters.set("rate_hz", rate_hz);
+            self.messages.send(tremolo::Message::ResetPhase)?;
+        }
+
+        if let Some(depth) = self
+            .config
+            .depth_slider
+            .and_then(|slider| midi::latest_slider_value(slider, 0.0, 1.0, messages))
+        {
+            self.messages.send(tremolo::Message::SetDepth(depth))?;
+            self.parameters.set("depth", depth);
+        }
+
+        Ok(())
+    }
+}
+
+impl Effect for Tremolo {
+    fn process(
+        &mut self,
+        midi_messages: &[midi::Message],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        self.handle_midi_messages(midi_messages)?;
+        self.unit.process(input, output)
+    }
+
+    fn parameters(&self) -> SharedParameters {
+        self.parameters.clone()
+    }
+}
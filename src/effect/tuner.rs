@@ -0,0 +1,265 @@
+use crate::{
+    audio::midi, audio_unit::PitchDetector, config::TunerConfig, effect::Effect, util, Result,
+};
+use anyhow::anyhow;
+use cpal::StreamConfig;
+use std::{convert::TryFrom, fmt, sync::mpsc::Sender};
+use wmidi::{MidiMessage, Note, U14, U7};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Reading {
+    note: Note,
+    cents: i32,
+}
+
+impl Reading {
+    fn new(frequency: f32, reference_hz: f32) -> Option<Self> {
+        let note_number = 69.0 + 12.0 * (frequency / reference_hz).log2();
+        let nearest = note_number.round();
+
+        if (0.0..=127.0).contains(&nearest) {
+            Some(Self {
+                note: Note::from_u8_lossy(nearest as u8),
+                cents: ((note_number - nearest) * 100.0).round() as i32,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// The deviation as a pitch bend, where the full range of the wheel is ±50 cents.
+    fn pitch_bend(&self) -> U14 {
+        let center = 0x2000 as f32;
+        let value = center + self.cents as f32 / 50.0 * (center - 1.0);
+        U14::try_from(value.round() as u16).unwrap_or(U14::MAX)
+    }
+}
+
+impl fmt::Display for Reading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:+} cents", self.note, self.cents)
+    }
+}
+
+/// A chromatic tuner. While it is on, the input is analysed several times per second, and the
+/// nearest note and its deviation in cents are printed whenever they change.
+pub struct Tuner {
+    config: TunerConfig,
+    is_on: bool,
+    detector: PitchDetector,
+    channels: usize,
+    window: Vec<f32>,
+    // `window` unrolled so that the oldest sample comes first, kept to avoid allocating
+    unrolled: Vec<f32>,
+    position: usize,
+    detection_interval: usize,
+    samples_until_detection: usize,
+    last_reading: Option<Reading>,
+    midi_output: Option<Sender<MidiMessage<'static>>>,
+}
+
+impl Tuner {
+    const DETECTIONS_PER_SECOND: u32 = 10;
+    const MIN_WINDOW_SIZE: usize = 512;
+    const VELOCITY: U7 = U7::MAX;
+
+    pub fn new(config: TunerConfig, stream_config: &StreamConfig) -> Result<Self> {
+        Self::validate_config(&config)?;
+
+        let sample_rate = stream_config.sample_rate.0;
+        let detection_interval = (sample_rate / Self::DETECTIONS_PER_SECOND) as usize;
+        let midi_output = config
+            .midi_output
+            .as_ref()
+            .map(|output| midi::connect_output(&output.port))
+            .transpose()?;
+
+        Ok(Self {
+            is_on: false,
+            detector: PitchDetector::new(
+                sample_rate as f32,
+                config.window_size,
+                PitchDetector::DEFAULT_THRESHOLD,
+            ),
+            channels: stream_config.channels as usize,
+            window: vec![0.0; config.window_size],
+            unrolled: vec![0.0; config.window_size],
+            position: 0,
+            detection_interval,
+            samples_until_detection: detection_interval,
+            last_reading: None,
+            midi_output,
+            config,
+        })
+    }
+
+    fn validate_config(config: &TunerConfig) -> Result<()> {
+        if config.window_size >= Self::MIN_WINDOW_SIZE && config.reference_hz > 0.0 {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid tuner config: {:#?}", config))
+        }
+    }
+
+    fn handle_midi_messages(&mut self, messages: &[midi::Message]) -> Result<()> {
+        let toggle = self.config.toggle;
+
+        for message in messages {
+            match message.message {
+                MidiMessage::NoteOn(channel, note, _)
+                    if channel == toggle.channel && note == toggle.note =>
+                {
+                    self.toggle()?;
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn toggle(&mut self) -> Result<()> {
+        self.is_on = !self.is_on;
+        util::log::print(format!("tuner: {}", if self.is_on { "on" } else { "off" }));
+
+        if !self.is_on {
+            self.update_reading(None)?;
+            util::zero_slice(&mut self.window);
+        }
+
+        Ok(())
+    }
+
+    fn analyse(&mut self, input: &[f32]) -> Result<()> {
+        for frame in input.chunks(self.channels) {
+            self.window[self.position] = frame.iter().sum::<f32>() / self.channels as f32;
+            self.position = (self.position + 1) % self.window.len();
+            self.samples_until_detection -= 1;
+
+            if self.samples_until_detection == 0 {
+                self.samples_until_detection = self.detection_interval;
+                self.detect()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn detect(&mut self) -> Result<()> {
+        let (newest, oldest) = self.window.split_at(self.position);
+        self.unrolled[..oldest.len()].copy_from_slice(oldest);
+        self.unrolled[oldest.len()..].copy_from_slice(newest);
+
+        let reading = self
+            .detector
+            .detect(&self.unrolled)
+            .and_then(|frequency| Reading::new(frequency, self.config.reference_hz));
+
+        // keep showing the last note while it rings out, rather than flickering between readings
+        if reading.is_some() {
+            self.update_reading(reading)?;
+        }
+
+        Ok(())
+    }
+
+    fn update_reading(&mut self, reading: Option<Reading>) -> Result<()> {
+        if reading == self.last_reading {
+            return Ok(());
+        }
+
+        if let Some(reading) = reading {
+            util::log::print(format!("tuner: {}", reading));
+        }
+
+        if let (Some(sender), Some(output)) = (&self.midi_output, &self.config.midi_output) {
+            let channel = output.channel;
+            let last_note = self.last_reading.map(|reading| reading.note);
+            let note = reading.map(|reading| reading.note);
+
+            if last_note != note {
+                if let Some(last_note) = last_note {
+                    sender.send(MidiMessage::NoteOff(channel, last_note, U7::MIN))?;
+                }
+                if let Some(note) = note {
+                    sender.send(MidiMessage::NoteOn(channel, note, Self::VELOCITY))?;
+                }
+            }
+
+            if let Some(reading) = reading {
+                sender.send(MidiMessage::PitchBendChange(channel, reading.pitch_bend()))?;
+            }
+        }
+
+        self.last_reading = reading;
+
+        Ok(())
+    }
+}
+
+impl Effect for Tuner {
+    fn process(
+        &mut self,
+        midi_messages: &[midi::Message],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        self.handle_midi_messages(midi_messages)?;
+
+        if !self.is_on {
+            output.copy_from_slice(input);
+            return Ok(());
+        }
+
+        self.analyse(input)?;
+
+        if self.config.mute {
+            util::zero_slice(output);
+        } else {
+            output.copy_from_slice(input);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reading() {
+        assert_eq!(
+            Reading::new(440.0, 440.0),
+            Some(Reading {
+                note: Note::A4,
+                cents: 0
+            })
+        );
+
+        // 20 cents sharp of E2 is still E2
+        let reading = Reading::new(82.41 * 2.0_f32.powf(0.2 / 12.0), 440.0).unwrap();
+        assert_eq!(reading.note, Note::E2);
+        assert_eq!(reading.cents, 20);
+
+        // past a quarter tone sharp, it's flat of the next note instead
+        let reading = Reading::new(82.41 * 2.0_f32.powf(0.6 / 12.0), 440.0).unwrap();
+        assert_eq!(reading.note, Note::F2);
+        assert_eq!(reading.cents, -40);
+    }
+
+    #[test]
+    fn test_pitch_bend() {
+        let in_tune = Reading {
+            note: Note::A4,
+            cents: 0,
+        };
+        assert_eq!(u16::from(in_tune.pitch_bend()), 0x2000);
+
+        let sharp = Reading {
+            note: Note::A4,
+            cents: 50,
+        };
+        assert_eq!(u16::from(sharp.pitch_bend()), 0x3FFF);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{
+    audio::midi,
+    audio_unit::{
+        self, biquad,
+        wah::{self, Mode},
+        AudioUnit,
+    },
+    config::WahConfig,
+    effect::{Effect, SharedParameters},
+    Result,
+};
+use anyhow::anyhow;
+use cpal::StreamConfig;
+use std::sync::mpsc::Sender;
+
+pub struct Wah {
+    config: WahConfig,
+    unit: audio_unit::Wah,
+    messages: Sender<wah::Message>,
+    parameters: SharedParameters,
+}
+
+impl Wah {
+    const MIN_Q: f32 = 0.5;
+
+    pub fn new(config: WahConfig, stream_config: &StreamConfig) -> Result<Self> {
+        Self::validate_config(&config)?;
+
+        let parameters = wah::Parameters {
+            mode: config.mode,
+            kind: config.filter,
+            min_hz: config.min_hz,
+            max_hz: config.max_hz,
+            q: config.q,
+            sensitivity: config.sensitivity,
+            attack_ms: config.attack_ms,
+            release_ms: config.release_ms,
+        };
+        let (unit, messages) = audio_unit::Wah::new(stream_config, parameters);
+
+        let mut parameters =
+            SharedParameters::new(&[("sensitivity", config.sensitivity), ("q", config.q)]);
+        if config.expression.is_some() {
+            // where the pedal is isn't known until it moves
+            parameters.add("expression", 0.0);
+        }
+
+        Ok(Self {
+            config,
+            unit,
+            messages,
+            parameters,
+        })
+    }
+
+    fn validate_config(config: &WahConfig) -> Result<()> {
+        let filter_is_valid = matches!(
+            config.filter,
+            biquad::Kind::BandPass | biquad::Kind::LowPass
+        );
+        let has_expression = config.mode == Mode::Auto || config.expression.is_some();
+
+        if filter_is_valid
+            && has_expression
+            && config.min_hz > 0.0
+            && config.min_hz <= config.max_hz
+            && config.q >= Self::MIN_Q
+            && config.q <= config.max_q
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid wah config: {:#?}", config))
+        }
+    }
+
+    fn handle_midi_messages(&mut self, messages: &[midi::Message]) -> Result<()> {
+        if let Some(position) = self
+            .config
+            .expression
+            .and_then(|slider| midi::latest_slider_value(slider, 0.0, 1.0, messages))
+        {
+            self.messages.send(wah::Message::SetPosition(position))?;
+            self.parameters.set("expression", position);
+        }
+
+        if let Some(sensitivity) = self.config.sensitivity_slider.and_then(|slider| {
+            midi::latest_slider_value(slider, 0.0, self.config.max_sensitivity, messages)
+        }) {
+            self.messages
+                .send(wah::Message::SetSensitivity(sensitivity))?;
+            self.parameters.set("sensitivity", sensitivity);
+        }
+
+        if let Some(q) = self.config.q_slider.and_then(|slider| {
+            midi::latest_slider_value(slider, Self::MIN_Q, self.config.max_q, messages)
+        }) {
+            self.messages.send(wah::Message::SetQ(q))?;
+            self.parameters.set("q", q);
+        }
+
+        Ok(())
+    }
+}
+
+impl Effect for Wah {
+    fn process(
+        &mut self,
+        midi_messages: &[midi::Message],
+        input: &[f32],
+        output: &mut [f32],
+    ) -> Result<()> {
+        self.handle_midi_messages(midi_messages)?;
+        self.unit.process(input, output)
+    }
+
+    fn parameters(&self) -> SharedParameters {
+        self.parameters.clone()
+    }
+}
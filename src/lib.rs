@@ -3,6 +3,7 @@ pub mod audio_unit;
 pub mod config;
 pub mod effect;
 pub mod ring_buffer;
+pub mod tui;
 
 mod result;
 mod util;
@@ -2,16 +2,28 @@ use audio::midi;
 use pedals::{
     audio,
     effect::{Effect, Pipeline},
-    Config, Result,
+    tui, Config, Result,
 };
-use std::{env, fs};
+use std::{env, fs, time::Duration};
+
+struct Args {
+    config_path: Option<String>,
+    /// Show the terminal UI instead of printing messages.
+    tui: bool,
+}
 
 fn main() -> Result<()> {
-    let config = config()?;
+    let args = args();
+    let yaml = args.config_path.as_deref().map(read_config).transpose()?;
+    let config = yaml
+        .as_deref()
+        .map(Config::from)
+        .transpose()?
+        .unwrap_or_default();
 
     let (input_device, output_device) = audio::devices(&config.audio)?;
     let stream_config = audio::config(&input_device)?;
-    let pipeline = Pipeline::from(&config, &stream_config)?.boxed();
+    let pipeline = Pipeline::from(&config, &stream_config)?;
 
     let midi_port_names = midi::port_names()?;
 
@@ -22,25 +34,62 @@ fn main() -> Result<()> {
         );
     }
 
-    audio::run(
+    if !args.tui {
+        if config.meters.print_interval_ms > 0 {
+            let interval = Duration::from_millis(config.meters.print_interval_ms as u64);
+            pipeline.statuses().print_levels(interval);
+        }
+
+        return audio::run(
+            config.audio.latency_ms,
+            &input_device,
+            &output_device,
+            &stream_config,
+            &config.midi,
+            pipeline.boxed(),
+        );
+    }
+
+    let statuses = pipeline.statuses();
+    let bypass = pipeline.bypass();
+
+    let midi_input = match &config.midi.port {
+        Some(port_name) => Some(midi::listen_for_input(port_name)?),
+        None => None,
+    };
+    let (midi_monitor, midi_messages) = tui::MidiMonitor::new(midi_input);
+
+    let _streams = audio::start(
         config.audio.latency_ms,
         &input_device,
         &output_device,
         &stream_config,
-        &config.midi,
-        pipeline,
+        Some(midi_messages),
+        pipeline.boxed(),
+    )?;
+
+    tui::run(
+        &config,
+        yaml.as_deref(),
+        stream_config.sample_rate.0,
+        &statuses,
+        &bypass,
+        &midi_monitor,
     )
 }
 
-fn config() -> Result<Config> {
-    let args: Vec<String> = env::args().collect();
-    let config = args.get(1).map(|path| config_from_path(path)).transpose()?;
-    Ok(config.unwrap_or_default())
+fn args() -> Args {
+    let (flags, paths): (Vec<String>, Vec<String>) =
+        env::args().skip(1).partition(|arg| arg.starts_with("--"));
+
+    Args {
+        config_path: paths.into_iter().next(),
+        tui: flags.iter().any(|flag| flag == "--tui"),
+    }
 }
 
-fn config_from_path(path: &str) -> Result<Config> {
+fn read_config(path: &str) -> Result<String> {
     println!("Reading config from {}", path);
 
-    let contents = fs::read_to_string(path)?;
-    Config::from(&contents)
+    Ok(fs::read_to_string(path)?)
 }
@@ -0,0 +1,256 @@
+use crate::{
+    config::{MidiSlider, NoteOn},
+    Result,
+};
+use serde_yaml::{Mapping, Value};
+use wmidi::U7;
+
+/// A key which sends the same note as a MIDI mapping in the config.
+#[derive(Debug)]
+pub struct Binding {
+    pub key: char,
+    /// Where the note is used in the config, eg. "Looper tracks 1 toggle".
+    pub name: String,
+    pub note_on: NoteOn,
+}
+
+/// A slider mapping in the config, which the arrow keys can move as though it were a controller.
+#[derive(Debug)]
+pub struct Slider {
+    /// Where the slider is used in the config, eg. "Delay mix_slider".
+    pub name: String,
+    pub slider: MidiSlider,
+    /// The last value sent from the keyboard. Sliders start in the middle, since where the
+    /// controller was left isn't known.
+    pub value: U7,
+}
+
+impl Slider {
+    const STEP: u8 = 8;
+
+    /// Moves the slider up or down a step, and returns its new value.
+    pub fn step(&mut self, up: bool) -> U7 {
+        let value = u8::from(self.value);
+        let value = if up {
+            value.saturating_add(Self::STEP).min(u8::from(U7::MAX))
+        } else {
+            value.saturating_sub(Self::STEP)
+        };
+
+        self.value = U7::from_u8_lossy(value);
+        self.value
+    }
+}
+
+/// The keys which are bound, in order. `q` is left for quitting.
+const KEYS: &str = "1234567890abcdefghijklmnoprstuvwxyz";
+
+/// Finds every note mapping in the config, and binds a key to each distinct note.
+pub fn from(yaml: &str) -> Result<Vec<Binding>> {
+    let notes = find(yaml, note_on, |existing, note_on| {
+        existing.channel == note_on.channel && existing.note == note_on.note
+    })?;
+
+    Ok(KEYS
+        .chars()
+        .zip(notes)
+        .map(|(key, (note_on, names))| Binding {
+            key,
+            name: names.join(", "),
+            note_on,
+        })
+        .collect())
+}
+
+/// Finds every distinct slider mapping in the config.
+pub fn sliders(yaml: &str) -> Result<Vec<Slider>> {
+    let sliders = find(yaml, slider, |existing, slider| {
+        existing.channel == slider.channel && existing.control_change == slider.control_change
+    })?;
+
+    Ok(sliders
+        .into_iter()
+        .map(|(slider, names)| Slider {
+            name: names.join(", "),
+            slider,
+            value: U7::from_u8_lossy(64),
+        })
+        .collect())
+}
+
+/// Finds the mappings in the config which `parse` recognises, along with where each is used.
+/// Mappings which are the same by `is_same` are combined.
+fn find<T>(
+    yaml: &str,
+    parse: fn(&Mapping) -> Option<T>,
+    is_same: fn(&T, &T) -> bool,
+) -> Result<Vec<(T, Vec<String>)>> {
+    let mut found: Vec<(T, Vec<String>)> = vec![];
+    find_in(
+        &serde_yaml::from_str(yaml)?,
+        &mut vec![],
+        &mut |mapping, name| {
+            let mapping = match parse(mapping) {
+                Some(mapping) => mapping,
+                None => return false,
+            };
+
+            match found
+                .iter_mut()
+                .find(|(existing, _)| is_same(existing, &mapping))
+            {
+                Some((_, names)) => names.push(name),
+                None => found.push((mapping, vec![name])),
+            }
+            true
+        },
+    );
+
+    Ok(found)
+}
+
+/// Walks the config, passing each mapping to `visit` with its path, and looking inside the ones
+/// which it doesn't take.
+fn find_in(value: &Value, path: &mut Vec<String>, visit: &mut dyn FnMut(&Mapping, String) -> bool) {
+    match value {
+        Value::Mapping(mapping) => {
+            if visit(mapping, path.join(" ")) {
+                return;
+            }
+
+            for (key, value) in mapping {
+                // effects are named by their type, so leave out the list they're in
+                let segment = match key.as_str() {
+                    Some("effects") => None,
+                    Some(key) => Some(key.to_string()),
+                    None => continue,
+                };
+
+                path.extend(segment.clone());
+                find_in(value, path, visit);
+                if segment.is_some() {
+                    path.pop();
+                }
+            }
+        }
+        Value::Sequence(sequence) => {
+            for (index, value) in sequence.iter().enumerate() {
+                let segment = value
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .map_or_else(|| (index + 1).to_string(), str::to_string);
+
+                path.push(segment);
+                find_in(value, path, visit);
+                path.pop();
+            }
+        }
+        _ => (),
+    }
+}
+
+fn note_on(mapping: &Mapping) -> Option<NoteOn> {
+    parse_with_keys(mapping, "note")
+}
+
+fn slider(mapping: &Mapping) -> Option<MidiSlider> {
+    parse_with_keys(mapping, "control_change")
+}
+
+/// Parses a mapping which has exactly a channel and `key`.
+fn parse_with_keys<T: serde::de::DeserializeOwned>(mapping: &Mapping, key: &str) -> Option<T> {
+    let is_match = mapping.len() == 2
+        && mapping.contains_key(&Value::from("channel"))
+        && mapping.contains_key(&Value::from(key));
+
+    if is_match {
+        serde_yaml::from_value(Value::Mapping(mapping.clone())).ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bindings_from_config() {
+        let yaml = "
+tempo:
+  tap_tempo:
+    channel: 1
+    note: 60
+effects:
+  - type: Delay
+    delay_ms: 300
+    tap_tempo:
+      channel: 1
+      note: 60
+    mix_slider:
+      channel: 1
+      control_change: 3
+  - type: Looper
+    tracks:
+      - toggle:
+          channel: 1
+          note: 61
+      - toggle:
+          channel: 1
+          note: 62
+";
+        let bindings = from(yaml).unwrap();
+        let names = bindings
+            .iter()
+            .map(|binding| (binding.key, binding.name.as_str()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            vec![
+                ('1', "tempo tap_tempo, Delay tap_tempo"),
+                ('2', "Looper tracks 1 toggle"),
+                ('3', "Looper tracks 2 toggle"),
+            ]
+        );
+        assert_eq!(u8::from(bindings[2].note_on.note), 62);
+    }
+
+    #[test]
+    fn test_sliders_from_config() {
+        let yaml = "
+effects:
+  - type: Delay
+    mix_slider:
+      channel: 1
+      control_change: 3
+  - type: Tremolo
+    depth_slider:
+      channel: 1
+      control_change: 3
+    rate_slider:
+      channel: 2
+      control_change: 3
+";
+        let mut sliders = sliders(yaml).unwrap();
+        let names = sliders
+            .iter()
+            .map(|slider| slider.name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            vec![
+                "Delay mix_slider, Tremolo depth_slider",
+                "Tremolo rate_slider"
+            ]
+        );
+        assert_eq!(u8::from(sliders[0].step(true)), 72);
+        assert_eq!(u8::from(sliders[1].step(false)), 56);
+
+        for _ in 0..20 {
+            sliders[0].step(true);
+        }
+        assert_eq!(sliders[0].value, U7::MAX);
+    }
+}
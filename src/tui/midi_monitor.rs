@@ -0,0 +1,85 @@
+use crate::{audio::midi::Message, Result};
+use anyhow::anyhow;
+use std::{
+    collections::VecDeque,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Instant,
+};
+use wmidi::MidiMessage;
+
+/// Passes MIDI messages on to the pipeline, keeping the most recent ones for display. Messages can
+/// also be sent from the keyboard, as though they came from a controller.
+pub struct MidiMonitor {
+    sender: Sender<Message>,
+    recent: Arc<Mutex<VecDeque<String>>>,
+    start: Instant,
+}
+
+impl MidiMonitor {
+    const MAX_RECENT: usize = 50;
+
+    /// Returns the monitor, and the receiver which the pipeline should read messages from.
+    pub fn new(input: Option<Receiver<Message>>) -> (Self, Receiver<Message>) {
+        let (sender, receiver) = mpsc::channel();
+        let recent = Arc::new(Mutex::new(VecDeque::new()));
+
+        if let Some(input) = input {
+            let sender = sender.clone();
+            let recent = recent.clone();
+
+            thread::spawn(move || {
+                for message in input {
+                    Self::remember(&recent, &message.message);
+
+                    if sender.send(message).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        (
+            Self {
+                sender,
+                recent,
+                start: Instant::now(),
+            },
+            receiver,
+        )
+    }
+
+    pub fn send(&self, message: MidiMessage<'static>) -> Result<()> {
+        Self::remember(&self.recent, &message);
+
+        let timestamp = self.start.elapsed().as_micros() as u64;
+        self.sender.send(Message::new(timestamp, message))?;
+
+        Ok(())
+    }
+
+    /// The most recent messages, newest first.
+    pub fn recent(&self) -> Result<Vec<String>> {
+        let recent = self
+            .recent
+            .lock()
+            .map_err(|_| anyhow!("recent MIDI messages lock was poisoned"))?;
+
+        Ok(recent.iter().cloned().collect())
+    }
+
+    fn remember(recent: &Mutex<VecDeque<String>>, message: &MidiMessage<'static>) {
+        // the clock sends 24 messages per beat, which would hide everything else
+        if let MidiMessage::TimingClock = message {
+            return;
+        }
+
+        if let Ok(mut recent) = recent.lock() {
+            recent.truncate(Self::MAX_RECENT - 1);
+            recent.push_front(format!("{:?}", message));
+        }
+    }
+}
@@ -0,0 +1,439 @@
+mod bindings;
+mod midi_monitor;
+mod parameters;
+
+pub use midi_monitor::MidiMonitor;
+
+use bindings::{Binding, Slider};
+
+use crate::{
+    audio_unit::looper::{Speed, Status, TrackStatus},
+    config,
+    effect::{Bypass, Level, SharedParameters, Statuses},
+    util::{self, log},
+    Result,
+};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Gauge, Paragraph, Wrap},
+    Frame, Terminal,
+};
+use std::{io, time::Duration};
+use wmidi::{MidiMessage, U7};
+
+/// Shows the pipeline while it runs, until `q` is pressed. A key is bound to each note in the
+/// config, which sends it to the pipeline, the arrow keys move the sliders in the config, and the
+/// function keys bypass effects.
+pub fn run(
+    config: &config::Config,
+    yaml: Option<&str>,
+    sample_rate: u32,
+    statuses: &Statuses,
+    bypass: &Bypass,
+    midi_monitor: &MidiMonitor,
+) -> Result<()> {
+    let bindings = yaml.map(bindings::from).transpose()?.unwrap_or_default();
+    let sliders = yaml.map(bindings::sliders).transpose()?.unwrap_or_default();
+    let parameters = yaml.map(parameters::from).transpose()?.unwrap_or_default();
+
+    terminal::enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    log::capture();
+
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    let mut ui = Ui {
+        config,
+        sample_rate,
+        bindings: &bindings,
+        sliders,
+        selected_slider: 0,
+        parameters: &parameters,
+        statuses,
+        bypass,
+        midi_monitor,
+        input: Level::default(),
+        output: Level::default(),
+        effects: vec![Level::default(); statuses.effects.len()],
+    };
+    let result = ui.run(&mut terminal);
+
+    log::release();
+    terminal::disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    result
+}
+
+struct Ui<'a> {
+    config: &'a config::Config,
+    sample_rate: u32,
+    bindings: &'a [Binding],
+    sliders: Vec<Slider>,
+    /// The slider which the left and right arrows move.
+    selected_slider: usize,
+    parameters: &'a [Vec<parameters::Written>],
+    statuses: &'a Statuses,
+    bypass: &'a Bypass,
+    midi_monitor: &'a MidiMonitor,
+    // the levels shown on the meters, whose peaks fall back slowly so that they can be read
+    input: Level,
+    output: Level,
+    effects: Vec<Level>,
+}
+
+impl<'a> Ui<'a> {
+    const REFRESH_INTERVAL: Duration = Duration::from_millis(50);
+    const PEAK_FALLOFF: f32 = 0.9;
+    const METER_FLOOR_DB: f32 = -60.0;
+    const MAX_BYPASS_KEYS: usize = 12;
+
+    fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        loop {
+            self.update_meters()?;
+
+            let loopers = self
+                .statuses
+                .loopers
+                .iter()
+                .map(|status| status.get())
+                .collect::<Result<Vec<_>>>()?;
+            let bpm = self
+                .statuses
+                .tempo
+                .get()?
+                .map(|tempo| 60.0 * tempo.beat_frequency());
+            let midi = self.midi_monitor.recent()?;
+
+            terminal.draw(|frame| self.draw(frame, &loopers, bpm, &midi))?;
+
+            if event::poll(Self::REFRESH_INTERVAL)? {
+                if let Event::Key(key) = event::read()? {
+                    // some terminals report releases too, which would send every key twice
+                    if key.kind == KeyEventKind::Press && !self.handle_key(key)? {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns false when the UI should quit.
+    fn handle_key(&mut self, key: KeyEvent) -> Result<bool> {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(false)
+            }
+            KeyCode::Char(key) => {
+                if let Some(binding) = self.bindings.iter().find(|binding| binding.key == key) {
+                    let note_on = binding.note_on;
+                    self.midi_monitor.send(MidiMessage::NoteOn(
+                        note_on.channel,
+                        note_on.note,
+                        U7::MAX,
+                    ))?;
+                    self.midi_monitor.send(MidiMessage::NoteOff(
+                        note_on.channel,
+                        note_on.note,
+                        U7::MIN,
+                    ))?;
+                }
+            }
+            KeyCode::Up => self.selected_slider = self.selected_slider.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected_slider =
+                    (self.selected_slider + 1).min(self.sliders.len().saturating_sub(1))
+            }
+            KeyCode::Left | KeyCode::Right => {
+                if let Some(slider) = self.sliders.get_mut(self.selected_slider) {
+                    let value = slider.step(key.code == KeyCode::Right);
+                    self.midi_monitor.send(MidiMessage::ControlChange(
+                        slider.slider.channel,
+                        slider.slider.control_change,
+                        value,
+                    ))?;
+                }
+            }
+            KeyCode::F(number) if number > 0 => self.bypass.toggle(number as usize - 1),
+            _ => (),
+        }
+
+        Ok(true)
+    }
+
+    fn update_meters(&mut self) -> Result<()> {
+        Self::update_level(&mut self.input, self.statuses.input.take()?);
+        Self::update_level(&mut self.output, self.statuses.output.take()?);
+
+        for (level, (_, meter)) in self.effects.iter_mut().zip(&self.statuses.effects) {
+            Self::update_level(level, meter.take()?);
+        }
+
+        Ok(())
+    }
+
+    fn update_level(shown: &mut Level, level: Level) {
+        *shown = Level {
+            peak: level.peak.max(shown.peak * Self::PEAK_FALLOFF),
+            ..level
+        };
+    }
+
+    fn draw<B: Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        loopers: &[Status],
+        bpm: Option<f32>,
+        midi: &[String],
+    ) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Length(4),
+                Constraint::Min(6),
+                Constraint::Length(12),
+            ])
+            .split(frame.size());
+        let middle = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(rows[1]);
+        let bottom = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Percentage(30),
+                Constraint::Percentage(40),
+                Constraint::Percentage(30),
+            ])
+            .split(rows[2]);
+
+        self.draw_meters(frame, rows[0], bpm);
+        self.draw_effects(frame, middle[0]);
+        self.draw_loopers(frame, middle[1], loopers);
+        Self::draw_lines(frame, bottom[0], "MIDI", midi.to_vec());
+        Self::draw_lines(
+            frame,
+            bottom[1],
+            "Messages",
+            log::recent(bottom[1].height as usize),
+        );
+        self.draw_keys(frame, bottom[2]);
+    }
+
+    fn draw_meters<B: Backend>(&self, frame: &mut Frame<B>, area: Rect, bpm: Option<f32>) {
+        let title = match bpm {
+            Some(bpm) => format!("pedals - {:.1} bpm - q to quit", bpm),
+            None => "pedals - no tempo - q to quit".to_string(),
+        };
+        let block = Block::default().borders(Borders::ALL).title(title);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(1), Constraint::Length(1)])
+            .split(inner);
+
+        for (row, (name, level)) in rows
+            .iter()
+            .zip(vec![("in ", self.input), ("out", self.output)])
+        {
+            frame.render_widget(Self::meter(name, level), *row);
+        }
+    }
+
+    fn meter(name: &str, level: Level) -> Gauge<'static> {
+        let db = util::gain_to_db(level.peak);
+        let ratio = ((db - Self::METER_FLOOR_DB) / -Self::METER_FLOOR_DB).clamp(0.0, 1.0);
+
+        Gauge::default()
+            .gauge_style(Style::default().fg(Self::level_color(level)))
+            .ratio(ratio as f64)
+            .label(format!(
+                "{} {:6.1} dB peak {:6.1} dB rms {} clips",
+                name,
+                db.max(Self::METER_FLOOR_DB),
+                util::gain_to_db(level.rms).max(Self::METER_FLOOR_DB),
+                level.clips
+            ))
+    }
+
+    fn level_color(level: Level) -> Color {
+        if level.peak >= 1.0 {
+            Color::Red
+        } else if util::gain_to_db(level.peak) > -6.0 {
+            Color::Yellow
+        } else {
+            Color::Green
+        }
+    }
+
+    fn draw_effects<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        let lines = self
+            .config
+            .effects
+            .iter()
+            .enumerate()
+            .map(|(index, effect)| {
+                let name = effect.name();
+                let live = self
+                    .statuses
+                    .parameters
+                    .get(index)
+                    .map(SharedParameters::get)
+                    .unwrap_or_default();
+                let parameters = parameters::show(
+                    self.parameters.get(index).map_or(&[][..], Vec::as_slice),
+                    &live,
+                );
+                let key = if index < Self::MAX_BYPASS_KEYS {
+                    format!("F{:<2} ", index + 1)
+                } else {
+                    "    ".to_string()
+                };
+                let (state, style) = if self.bypass.is_bypassed(index) {
+                    ("bypassed", Style::default().fg(Color::DarkGray))
+                } else {
+                    ("on", Style::default().fg(Color::Green))
+                };
+
+                let mut spans = vec![
+                    Span::raw(key),
+                    Span::styled(name, Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(" "),
+                    Span::styled(state, style),
+                ];
+
+                if let Some(level) = self.effects.get(index) {
+                    spans.push(Span::styled(
+                        format!(
+                            " {:.1} dB{}",
+                            util::gain_to_db(level.peak).max(Self::METER_FLOOR_DB),
+                            if level.clips > 0 { " clipped" } else { "" }
+                        ),
+                        Style::default().fg(Self::level_color(*level)),
+                    ));
+                }
+
+                spans.push(Span::raw(" "));
+                spans.push(Span::styled(parameters, Style::default().fg(Color::Gray)));
+
+                Spans::from(spans)
+            })
+            .collect::<Vec<_>>();
+
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(Block::default().borders(Borders::ALL).title("Effects"))
+                .wrap(Wrap { trim: false }),
+            area,
+        );
+    }
+
+    fn draw_loopers<B: Backend>(&self, frame: &mut Frame<B>, area: Rect, loopers: &[Status]) {
+        let lines = loopers
+            .iter()
+            .enumerate()
+            .flat_map(|(index, status)| {
+                let mut header = format!("looper {}", index + 1);
+                if status.reverse {
+                    header.push_str(" reversed");
+                }
+
+                let tracks = status
+                    .tracks
+                    .iter()
+                    .enumerate()
+                    .map(|(track, track_status)| self.track_line(track, track_status));
+
+                std::iter::once(Spans::from(Span::styled(
+                    header,
+                    Style::default().add_modifier(Modifier::BOLD),
+                )))
+                .chain(tracks)
+                .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Looper")),
+            area,
+        );
+    }
+
+    fn track_line(&self, index: usize, status: &TrackStatus) -> Spans<'static> {
+        let seconds = |frames: usize| frames as f32 / self.sample_rate as f32;
+        let mut line = format!(
+            "{} {:?}{} {:.1}/{:.1}s",
+            index + 1,
+            status.state,
+            if status.is_waiting { " (waiting)" } else { "" },
+            seconds(status.position),
+            seconds(status.length),
+        );
+
+        if status.is_muted {
+            line.push_str(" muted");
+        }
+        if status.undo_layers > 0 || status.redo_layers > 0 {
+            line.push_str(&format!(
+                " undo {} redo {}",
+                status.undo_layers, status.redo_layers
+            ));
+        }
+
+        if status.speed != Speed::Normal {
+            line.push_str(&format!(" {:?} speed", status.speed));
+        }
+
+        Spans::from(line)
+    }
+
+    fn draw_keys<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        let mut lines = self
+            .bindings
+            .iter()
+            .map(|binding| format!("{} {}", binding.key, binding.name))
+            .collect::<Vec<_>>();
+
+        if !self.sliders.is_empty() {
+            lines.push("up/down picks a slider, left/right moves it".to_string());
+            lines.extend(self.sliders.iter().enumerate().map(|(index, slider)| {
+                format!(
+                    "{} {} {}",
+                    if index == self.selected_slider {
+                        ">"
+                    } else {
+                        " "
+                    },
+                    slider.name,
+                    u8::from(slider.value)
+                )
+            }));
+        }
+
+        Self::draw_lines(frame, area, "Keys", lines);
+    }
+
+    fn draw_lines<B: Backend>(frame: &mut Frame<B>, area: Rect, title: &str, lines: Vec<String>) {
+        let lines = lines.into_iter().map(Spans::from).collect::<Vec<_>>();
+
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title.to_string()),
+            ),
+            area,
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use crate::Result;
+use serde_yaml::Value;
+
+/// A parameter's name and value, as written in the config.
+pub type Written = (String, String);
+
+/// The parameters written in the config for each effect, eg. `delay_ms: 300`. MIDI mappings and
+/// other nested settings are left out.
+pub fn from(yaml: &str) -> Result<Vec<Vec<Written>>> {
+    let config: Value = serde_yaml::from_str(yaml)?;
+    let effects = config
+        .get("effects")
+        .and_then(Value::as_sequence)
+        .map_or(&[][..], Vec::as_slice);
+
+    Ok(effects.iter().map(parameters).collect())
+}
+
+/// Shows the parameters, eg. "delay_ms: 300, ping_pong: true", with the live values in place of
+/// the written ones. Live parameters which aren't written in the config are shown after them.
+pub fn show(written: &[Written], live: &[(&str, f32)]) -> String {
+    let live_value = |name: &str| {
+        live.iter()
+            .find(|(live_name, _)| *live_name == name)
+            .map(|(_, value)| format_value(*value))
+    };
+
+    written
+        .iter()
+        .map(|(name, value)| {
+            let value = live_value(name).unwrap_or_else(|| value.clone());
+            format!("{}: {}", name, value)
+        })
+        .chain(
+            live.iter()
+                .filter(|(name, _)| !written.iter().any(|(written, _)| written == name))
+                .map(|(name, value)| format!("{}: {}", name, format_value(*value))),
+        )
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Rounds to two decimal places, leaving off trailing zeros.
+fn format_value(value: f32) -> String {
+    let value = format!("{:.2}", value);
+    value
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+fn parameters(effect: &Value) -> Vec<Written> {
+    let mapping = match effect.as_mapping() {
+        Some(mapping) => mapping,
+        None => return vec![],
+    };
+
+    mapping
+        .iter()
+        .filter_map(|(key, value)| {
+            let key = key.as_str().filter(|key| *key != "type")?;
+            let value = match value {
+                Value::Bool(value) => value.to_string(),
+                Value::Number(value) => value.to_string(),
+                Value::String(value) => value.clone(),
+                _ => return None,
+            };
+
+            Some((key.to_string(), value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parameters_from_config() {
+        let yaml = "
+effects:
+  - type: Delay
+    delay_ms: 300
+    mix_slider:
+      channel: 1
+      control_change: 3
+    ping_pong: true
+  - type: Transparent
+";
+
+        let parameters = from(yaml).unwrap();
+        assert_eq!(show(&parameters[0], &[]), "delay_ms: 300, ping_pong: true");
+        assert_eq!(show(&parameters[1], &[]), "");
+    }
+
+    #[test]
+    fn test_live_values_replace_written_ones() {
+        let written = vec![
+            ("delay_ms".to_string(), "300".to_string()),
+            ("ping_pong".to_string(), "true".to_string()),
+        ];
+
+        assert_eq!(
+            show(&written, &[("delay_ms", 100.0), ("mix", 0.126)]),
+            "delay_ms: 100, ping_pong: true, mix: 0.13"
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use std::{collections::VecDeque, sync::Mutex};
+
+/// Messages from the effects, kept while the terminal UI is showing instead of being printed over
+/// it. `None` when they are printed as usual.
+static CAPTURED: Mutex<Option<VecDeque<String>>> = Mutex::new(None);
+
+const MAX_CAPTURED: usize = 100;
+
+pub fn print(message: impl Into<String>) {
+    let message = message.into();
+
+    if !capture_message(&message) {
+        println!("{}", message);
+    }
+}
+
+pub fn error(message: impl Into<String>) {
+    let message = message.into();
+
+    if !capture_message(&message) {
+        eprintln!("{}", message);
+    }
+}
+
+/// Keeps messages instead of printing them, until `release` is called.
+pub fn capture() {
+    if let Ok(mut captured) = CAPTURED.lock() {
+        *captured = Some(VecDeque::new());
+    }
+}
+
+/// Goes back to printing messages, and prints any which were kept.
+pub fn release() {
+    let captured = CAPTURED
+        .lock()
+        .ok()
+        .and_then(|mut captured| captured.take());
+
+    for message in captured.into_iter().flatten() {
+        println!("{}", message);
+    }
+}
+
+/// The most recent messages which were kept, oldest first.
+pub fn recent(count: usize) -> Vec<String> {
+    CAPTURED
+        .lock()
+        .ok()
+        .and_then(|captured| {
+            captured.as_ref().map(|captured| {
+                captured
+                    .iter()
+                    .skip(captured.len().saturating_sub(count))
+                    .cloned()
+                    .collect()
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// Keeps the message if messages are being captured. Returns false if it should be printed.
+fn capture_message(message: &str) -> bool {
+    match CAPTURED.lock() {
+        Ok(mut captured) => match captured.as_mut() {
+            Some(captured) => {
+                if captured.len() == MAX_CAPTURED {
+                    captured.pop_front();
+                }
+                captured.push_back(message.to_string());
+                true
+            }
+            None => false,
+        },
+        Err(_) => false,
+    }
+}
@@ -1,4 +1,7 @@
+pub mod log;
 pub mod number;
+pub mod random;
+pub mod wav;
 
 use cpal::StreamConfig;
 
@@ -12,3 +15,11 @@ pub fn ms_in_samples(stream_config: &StreamConfig, ms: u32) -> usize {
     let samples = (ms as f32 / 1_000.0) * stream_config.sample_rate.0 as f32;
     samples as usize * stream_config.channels as usize
 }
+
+pub fn gain_to_db(gain: f32) -> f32 {
+    20.0 * gain.max(1e-9).log10()
+}
+
+pub fn db_to_gain(db: f32) -> f32 {
+    10.0_f32.powf(db / 20.0)
+}
@@ -0,0 +1,97 @@
+use crate::Result;
+use anyhow::anyhow;
+use cpal::StreamConfig;
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use std::path::Path;
+
+/// Reads a WAV file as interleaved samples with the stream's channel count. Files with a different
+/// number of channels are mixed down to mono, then copied to each channel.
+pub fn read(path: impl AsRef<Path>, stream_config: &StreamConfig) -> Result<Vec<f32>> {
+    let path = path.as_ref();
+    let mut reader = WavReader::open(path)?;
+    let spec = reader.spec();
+
+    if spec.sample_rate != stream_config.sample_rate.0 {
+        return Err(anyhow!(
+            "{} has a sample rate of {} Hz, but the stream's is {} Hz",
+            path.display(),
+            spec.sample_rate,
+            stream_config.sample_rate.0
+        ));
+    }
+
+    let samples = match spec.sample_format {
+        SampleFormat::Float => reader
+            .samples::<f32>()
+            .collect::<std::result::Result<Vec<_>, _>>()?,
+        SampleFormat::Int => {
+            let scale = 1.0 / (1_i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|sample| sample as f32 * scale))
+                .collect::<std::result::Result<Vec<_>, _>>()?
+        }
+    };
+
+    let file_channels = spec.channels as usize;
+    let channels = stream_config.channels as usize;
+
+    if file_channels == channels {
+        return Ok(samples);
+    }
+
+    Ok(samples
+        .chunks(file_channels)
+        .flat_map(|frame| {
+            let mono = frame.iter().sum::<f32>() / file_channels as f32;
+            std::iter::repeat_n(mono, channels)
+        })
+        .collect())
+}
+
+/// Writes interleaved samples to a 32 bit float WAV file.
+pub fn write(path: impl AsRef<Path>, stream_config: &StreamConfig, samples: &[f32]) -> Result<()> {
+    let spec = WavSpec {
+        channels: stream_config.channels,
+        sample_rate: stream_config.sample_rate.0,
+        bits_per_sample: 32,
+        sample_format: SampleFormat::Float,
+    };
+
+    let mut writer = WavWriter::create(path, spec)?;
+    for sample in samples {
+        writer.write_sample(*sample)?;
+    }
+    writer.finalize()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cpal::{BufferSize, SampleRate};
+    use std::fs;
+
+    fn stream_config(channels: u16) -> StreamConfig {
+        StreamConfig {
+            channels,
+            sample_rate: SampleRate(1_000),
+            buffer_size: BufferSize::Default,
+        }
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let path = std::env::temp_dir().join("pedals-test-write-and-read.wav");
+        let samples = vec![0.5, -0.5, 0.25, -0.25];
+
+        write(&path, &stream_config(2), &samples).unwrap();
+
+        assert_eq!(read(&path, &stream_config(2)).unwrap(), samples);
+        // each frame is mixed down and copied to the single channel
+        assert_eq!(read(&path, &stream_config(1)).unwrap(), vec![0.0, 0.0]);
+
+        fs::remove_file(&path).unwrap();
+    }
+}